@@ -0,0 +1,181 @@
+use std::io::{self, Cursor, Read};
+
+use crate::reader::Source;
+
+/// An archive of files, recognized by the magic bytes at the start of the input. Compressed
+/// archives, like `.tar.gz`, are recognized once they are decompressed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Archive {
+    Tar,
+    Zip,
+}
+
+// The tar magic is in the header of the first member, after its name and attributes
+const TAR_MAGIC: std::ops::Range<usize> = 257..262;
+
+/// A file inside an archive.
+pub struct Member {
+    /// The path of the file inside the archive.
+    pub path: String,
+    pub content: Vec<u8>,
+}
+
+impl Archive {
+    /// Checks whether the input is an archive, without consuming any of it.
+    pub fn detect(source: Source) -> io::Result<(Option<Archive>, Source)> {
+        // Waiting for a whole tar header would hold back the search of a slow stream of JSON
+        let (start, source) = source.peek(1)?;
+        if start.first().is_none_or(|b| b"{[\" \t\r\n".contains(b)) {
+            return Ok((None, source));
+        }
+
+        let (start, source) = source.peek(TAR_MAGIC.end)?;
+        let archive = if start.starts_with(b"PK\x03\x04") || start.starts_with(b"PK\x05\x06") {
+            Some(Archive::Zip)
+        } else if start.get(TAR_MAGIC) == Some(b"ustar") {
+            Some(Archive::Tar)
+        } else {
+            None
+        };
+        Ok((archive, source))
+    }
+
+    /// Reads the files in the archive in order, skipping directories and links, and calls
+    /// `on_member` with every file `include` accepts, until it returns false. Every file is read
+    /// whole, so it can be searched like any other input.
+    pub fn members(
+        self,
+        source: Source,
+        include: &dyn Fn(&str) -> bool,
+        on_member: &mut dyn FnMut(Member) -> bool,
+    ) -> io::Result<()> {
+        match self {
+            Archive::Tar => {
+                let reader: Box<dyn Read + '_> = match source {
+                    Source::Bytes(bytes) => Box::new(bytes),
+                    Source::Reader(reader) => reader,
+                };
+                let mut archive = tar::Archive::new(reader);
+                for entry in archive.entries()? {
+                    let mut entry = entry?;
+                    let path = entry.path()?.display().to_string();
+                    if !entry.header().entry_type().is_file() || !include(&path) {
+                        continue;
+                    }
+                    let mut content = Vec::new();
+                    entry.read_to_end(&mut content)?;
+                    if !on_member(Member { path, content }) {
+                        break;
+                    }
+                }
+            }
+            Archive::Zip => {
+                // The list of files is at the end of a zip, so a stream has to be read whole
+                let read;
+                let bytes = match source {
+                    Source::Bytes(bytes) => bytes,
+                    Source::Reader(mut reader) => {
+                        let mut content = Vec::new();
+                        reader.read_to_end(&mut content)?;
+                        read = content;
+                        &read
+                    }
+                };
+                let mut archive = zip::ZipArchive::new(Cursor::new(bytes))?;
+                for i in 0..archive.len() {
+                    let mut file = archive.by_index(i)?;
+                    let path = file.name().to_string();
+                    if !file.is_file() || !include(&path) {
+                        continue;
+                    }
+                    let mut content = Vec::new();
+                    file.read_to_end(&mut content)?;
+                    if !on_member(Member { path, content }) {
+                        break;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::{Archive, Member};
+    use crate::reader::Source;
+
+    fn tar() -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (path, content) in [("a.json", "{\"a\": 1}"), ("dir/b.txt", "b")] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            builder
+                .append_data(&mut header, path, content.as_bytes())
+                .unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    fn zip() -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default();
+        writer.add_directory("dir/", options).unwrap();
+        for (path, content) in [("dir/a.json", "{\"a\": 1}"), ("b.txt", "b")] {
+            writer.start_file(path, options).unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    fn members(source: Source, include: &dyn Fn(&str) -> bool) -> Vec<(String, String)> {
+        let (archive, source) = Archive::detect(source).unwrap();
+        let mut members = Vec::new();
+        archive
+            .unwrap()
+            .members(source, include, &mut |member: Member| {
+                let content = String::from_utf8(member.content).unwrap();
+                members.push((member.path, content));
+                true
+            })
+            .unwrap();
+        members
+    }
+
+    #[test]
+    fn test_members() {
+        let tar = tar();
+        let zip = zip();
+        let all = |_: &str| true;
+        let json = |path: &str| path.ends_with(".json");
+
+        assert_eq!(
+            members(Source::Bytes(&tar), &all),
+            vec![
+                ("a.json".to_string(), "{\"a\": 1}".to_string()),
+                ("dir/b.txt".to_string(), "b".to_string()),
+            ]
+        );
+        assert_eq!(
+            members(Source::Reader(Box::new(tar.as_slice())), &json),
+            vec![("a.json".to_string(), "{\"a\": 1}".to_string())]
+        );
+        assert_eq!(
+            members(Source::Reader(Box::new(zip.as_slice())), &all),
+            vec![
+                ("dir/a.json".to_string(), "{\"a\": 1}".to_string()),
+                ("b.txt".to_string(), "b".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_not_an_archive() {
+        let (archive, source) = Archive::detect(Source::Bytes(b"{\"a\": 1}")).unwrap();
+        assert_eq!(archive, None);
+        assert!(matches!(source, Source::Bytes(b"{\"a\": 1}")));
+    }
+}
@@ -1,6 +1,6 @@
 use clap::Parser;
 
-use crate::PrinterType;
+use crate::{BytesEncoding, CountScope, InputFormat, PrinterType};
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
@@ -11,7 +11,85 @@ use crate::PrinterType;
 ))]
 pub struct Args {
     pub pattern: String,
-    pub path: Option<String>,
+
+    /// Files or directories to search, `-` for stdin. Searches stdin without any.
+    pub paths: Vec<String>,
+
+    /// Searches the files inside directories, skipping hidden files and the ones ignored by
+    /// .gitignore files. Without --include, only the files with the extension of a known format
+    /// or of an archive are searched.
+    #[clap(short = 'r', long)]
+    pub recursive: bool,
+
+    /// Only searches the files in directories and archives with a name that matches the glob.
+    /// Without it, only the files with the extension of a known format are searched in them.
+    #[clap(long, value_name = "GLOB")]
+    pub include: Vec<String>,
+
+    /// Skips the files in directories and archives with a name that matches the glob.
+    #[clap(long, value_name = "GLOB")]
+    pub exclude: Vec<String>,
+
+    /// The format of the inputs. By default, it is picked from the extension of every file, and
+    /// anything else is read as JSON.
+    #[clap(long = "input", value_enum, value_name = "FORMAT")]
+    pub input_format: Option<InputFormat>,
+
+    /// Shortcut for --input=json5.
+    /// Accepts comments, trailing commas, unquoted keys and single-quoted strings in JSON. It is
+    /// already the default for .jsonc and .json5 files, and config files like tsconfig.json.
+    #[clap(long, visible_alias = "json5", conflicts_with = "input_format")]
+    pub jsonc: bool,
+
+    /// How the byte strings of CBOR and MessagePack inputs are printed.
+    #[clap(long, value_enum, value_name = "ENCODING", default_value = "base64")]
+    pub bytes: BytesEncoding,
+
+    /// Reads JSON Lines, where every line is a document. Matches are printed after the number of
+    /// their line, and invalid lines are reported and skipped.
+    #[clap(long, visible_alias = "jsonl")]
+    pub ndjson: bool,
+
+    /// Searches the JSON objects and arrays found inside every line of text, like the payloads of
+    /// log lines. Matches are printed after the number of their line and the text before the JSON.
+    #[clap(long, conflicts_with = "ndjson")]
+    pub extract: bool,
+
+    /// Reports the invalid documents and carries on, instead of stopping at the first one. In a
+    /// stream of JSON documents, reading starts again at the next line starting with `{` or `[`.
+    /// In other formats, the rest of the file is skipped.
+    #[clap(long)]
+    pub skip_invalid: bool,
+
+    /// Matches documents while they are parsed, keeping only the current path in memory, for files
+    /// too big to load. Matches are printed in the order they start in the document. Only works with
+    /// the path and only printers.
+    #[clap(long, conflicts_with_all = ["ndjson", "extract", "json", "context", "invert", "outermost", "innermost"])]
+    pub stream: bool,
+
+    /// Searches inside the string values that hold a JSON object or array, as if they were part of
+    /// the document. In the path, the parsed strings are followed by <json>.
+    #[clap(long, conflicts_with = "stream")]
+    pub parse_nested: bool,
+
+    /// Requires every input to be compressed with gzip, zstd, bzip2 or xz. Compressed inputs are
+    /// recognized and decompressed without it, this reports the ones that aren't instead of
+    /// searching them.
+    #[clap(short = 'z', long)]
+    pub decompress: bool,
+
+    /// Searches the files ignored by .gitignore files too.
+    #[clap(long)]
+    pub no_ignore: bool,
+
+    /// Prints the file name before every line of output. It is the default when searching more
+    /// than one file.
+    #[clap(short = 'H', long, conflicts_with = "no_filename")]
+    pub with_filename: bool,
+
+    /// Never prints the file name before the output.
+    #[clap(long)]
+    pub no_filename: bool,
 
     /// Shows N levels of parent context around the match.
     #[clap(short = 'C', long)]
@@ -38,5 +116,61 @@ pub struct Args {
     #[clap(short, long)]
     pub json: bool,
 
+    /// Only shows matches with a path of at least N keys and indexes.
+    #[clap(long, value_name = "N", default_value_t = 0)]
+    pub min_depth: usize,
+
+    /// Only shows matches with a path of at most N keys and indexes. Deeper values aren't
+    /// searched at all.
+    #[clap(long, value_name = "N")]
+    pub max_depth: Option<usize>,
+
+    /// Drops the matches nested inside another match, so every value is printed once.
+    #[clap(long, conflicts_with = "innermost")]
+    pub outermost: bool,
+
+    /// Drops the matches that contain another match, so every value is printed once.
+    #[clap(long)]
+    pub innermost: bool,
+
+    /// Prints how many matches there are, instead of the matches. They are counted in every file
+    /// by default, see --count-per.
+    #[clap(short = 'c', long)]
+    pub count: bool,
+
+    /// Prints what doesn't match. In a stream of documents, prints the documents without matches,
+    /// one per line. A single document is printed as JSON, with every match removed from it.
+    #[clap(short = 'v', long, conflicts_with_all = ["printer_group", "context", "count", "max_count"])]
+    pub invert: bool,
+
+    /// Prints the name of every input with a match, instead of the matches. Stops reading an input
+    /// at its first match.
+    #[clap(short = 'l', long, conflicts_with_all = ["files_without_match", "count", "invert"])]
+    pub files_with_matches: bool,
+
+    /// Prints the name of every input without any match, instead of the matches. Stops reading an
+    /// input at its first match.
+    #[clap(short = 'L', long, conflicts_with_all = ["count", "invert"])]
+    pub files_without_match: bool,
+
+    /// Stops after N matches, without reading any further. Matches are counted in every file by
+    /// default, see --count-per.
+    #[clap(short = 'm', long, value_name = "N")]
+    pub max_count: Option<usize>,
+
+    /// Whether --max-count and --count apply to every JSON document, every file, or all of them
+    /// together.
+    #[clap(long, value_enum, default_value = "file")]
+    pub count_per: CountScope,
+
+    /// Number of worker threads searching documents concurrently, shared by all the inputs, so
+    /// many small files are searched in parallel too. Defaults to the number of CPUs. The output
+    /// order doesn't depend on it. It is -J because -j is --json.
+    #[clap(short = 'J', long, value_name = "N")]
+    pub threads: Option<usize>,
+
+    /// Rejects documents nested more than N levels deep, instead of searching them.
+    #[clap(long, value_name = "N")]
+    pub max_depth_guard: Option<usize>,
 }
 
@@ -0,0 +1,133 @@
+use std::io::{self, Read};
+
+use crate::reader::Source;
+
+/// A compression format, recognized by the magic bytes at the start of the input.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Compression {
+    Gzip,
+    Zstd,
+    Bzip2,
+    Xz,
+}
+
+// Enough to tell all the formats apart
+const MAGIC_LEN: usize = 6;
+
+impl Compression {
+    /// Detects the format from the first bytes of the input. JSON can't start with any of them.
+    pub fn detect(start: &[u8]) -> Option<Compression> {
+        match start {
+            [0x1f, 0x8b, ..] => Some(Compression::Gzip),
+            [0x28, 0xb5, 0x2f, 0xfd, ..] => Some(Compression::Zstd),
+            [b'B', b'Z', b'h', ..] => Some(Compression::Bzip2),
+            [0xfd, b'7', b'z', b'X', b'Z', 0x00, ..] => Some(Compression::Xz),
+            _ => None,
+        }
+    }
+
+    /// Decompresses the whole input, including every member or frame when several compressed
+    /// files were concatenated, like rotated logs often are.
+    fn decoder<'a, R: Read + Send + 'a>(self, reader: R) -> io::Result<Box<dyn Read + Send + 'a>> {
+        Ok(match self {
+            Compression::Gzip => Box::new(flate2::read::MultiGzDecoder::new(reader)),
+            Compression::Zstd => Box::new(zstd::Decoder::new(reader)?),
+            Compression::Bzip2 => Box::new(bzip2::read::MultiBzDecoder::new(reader)),
+            Compression::Xz => Box::new(liblzma::read::XzDecoder::new_multi_decoder(reader)),
+        })
+    }
+}
+
+/// Decompresses the input while it is read, if it starts with the magic bytes of a compression
+/// format. With `force`, an input that isn't compressed is an error.
+pub fn decompress(source: Source, force: bool) -> io::Result<Source> {
+    let (start, source) = source.peek(MAGIC_LEN)?;
+    let compression = Compression::detect(&start);
+
+    match (compression, source) {
+        (None, _) if force => Err(io::Error::other("Not in a supported compressed format")),
+        (None, source) => Ok(source),
+        (Some(compression), Source::Bytes(bytes)) => {
+            Ok(Source::Reader(compression.decoder(bytes)?))
+        }
+        (Some(compression), Source::Reader(reader)) => {
+            Ok(Source::Reader(compression.decoder(reader)?))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+
+    use super::{decompress, Compression};
+    use crate::reader::Source;
+
+    const JSON: &str = "{\"a\": 1}\n{\"a\": 2}\n";
+
+    fn compress(compression: Compression, data: &[u8]) -> Vec<u8> {
+        match compression {
+            Compression::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(data).unwrap();
+                encoder.finish().unwrap()
+            }
+            Compression::Zstd => zstd::encode_all(data, 0).unwrap(),
+            Compression::Bzip2 => {
+                let mut encoder =
+                    bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+                encoder.write_all(data).unwrap();
+                encoder.finish().unwrap()
+            }
+            Compression::Xz => {
+                let mut encoder = liblzma::write::XzEncoder::new(Vec::new(), 6);
+                encoder.write_all(data).unwrap();
+                encoder.finish().unwrap()
+            }
+        }
+    }
+
+    fn read(source: Source, force: bool) -> Result<String, String> {
+        let mut content = String::new();
+        match decompress(source, force).map_err(|e| e.to_string())? {
+            Source::Reader(mut reader) => {
+                reader
+                    .read_to_string(&mut content)
+                    .map_err(|e| e.to_string())?;
+            }
+            Source::Bytes(bytes) => content = String::from_utf8(bytes.to_vec()).unwrap(),
+        }
+        Ok(content)
+    }
+
+    #[test]
+    fn test_decompress() {
+        for compression in [
+            Compression::Gzip,
+            Compression::Zstd,
+            Compression::Bzip2,
+            Compression::Xz,
+        ] {
+            // Two members, like concatenated rotated logs
+            let mut data = compress(compression, &JSON.as_bytes()[..9]);
+            data.extend(compress(compression, &JSON.as_bytes()[9..]));
+            assert_eq!(Compression::detect(&data), Some(compression));
+
+            assert_eq!(read(Source::Bytes(&data), true), Ok(JSON.to_string()));
+            let reader = Box::new(data.as_slice());
+            assert_eq!(read(Source::Reader(reader), false), Ok(JSON.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_not_compressed() {
+        let reader = Box::new(JSON.as_bytes());
+        assert_eq!(read(Source::Reader(reader), false), Ok(JSON.to_string()));
+        assert_eq!(read(Source::Bytes(b"1"), false), Ok("1".to_string()));
+        assert_eq!(
+            read(Source::Bytes(JSON.as_bytes()), true),
+            Err("Not in a supported compressed format".to_string())
+        );
+    }
+}
@@ -0,0 +1,175 @@
+use std::error::Error;
+
+// How much of a long line is shown on each side of an error
+const SNIPPET_CONTEXT: usize = 40;
+
+/// An error in one of the documents being searched.
+#[derive(Debug)]
+pub struct DocumentError {
+    message: String,
+    pub exit_code: i32,
+    // Where the error is in the input, from 1
+    line: Option<usize>,
+    column: Option<usize>,
+    snippet: Option<Snippet>,
+    // Where the error is in a binary input, from 0
+    byte_offset: Option<usize>,
+}
+
+/// The text around an error, to show it under the message.
+#[derive(Debug, PartialEq)]
+pub struct Snippet {
+    text: String,
+    // The character of the text where the error is
+    caret: usize,
+}
+
+impl Snippet {
+    /// Takes the part of a line around the byte at `column`, from 1.
+    pub fn new(line: &[u8], column: usize) -> Snippet {
+        let line = line.strip_suffix(b"\n").unwrap_or(line);
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let at = column.saturating_sub(1).min(line.len());
+        let start = at.saturating_sub(SNIPPET_CONTEXT);
+        let end = (at + SNIPPET_CONTEXT).min(line.len());
+
+        let before = String::from_utf8_lossy(&line[start..at]);
+        let text = String::from_utf8_lossy(&line[start..end]).replace('\t', " ");
+        Snippet {
+            text,
+            caret: before.chars().count(),
+        }
+    }
+
+    /// Finds the line of the text with the error, and takes the part around it. Lines and
+    /// columns are counted from 1.
+    pub fn find(text: &[u8], line: usize, column: usize) -> Option<Snippet> {
+        let line = text.split_inclusive(|&b| b == b'\n').nth(line.checked_sub(1)?)?;
+        Some(Snippet::new(line, column))
+    }
+}
+
+impl DocumentError {
+    pub fn new(message: String, exit_code: i32) -> Self {
+        DocumentError {
+            message,
+            exit_code,
+            line: None,
+            column: None,
+            snippet: None,
+            byte_offset: None,
+        }
+    }
+
+    /// The document is not valid JSON, or can't be searched.
+    pub fn invalid(message: String) -> Self {
+        Self::new(message, 3)
+    }
+
+    /// The document is not valid JSON, with the reason serde gives, but without its position.
+    pub fn json(err: &serde_json::Error) -> Self {
+        let reason = err.to_string();
+        let position = format!(" at line {} column {}", err.line(), err.column());
+        let reason = reason.strip_suffix(&position).unwrap_or(&reason);
+        Self::invalid(format!("Invalid JSON: {}", reason))
+    }
+
+    /// Says where the error is in the input, with the text around it if it is known.
+    pub fn at(mut self, line: usize, column: usize, snippet: Option<Snippet>) -> Self {
+        self.line = Some(line);
+        self.column = Some(column);
+        self.snippet = snippet;
+        self
+    }
+
+    /// Says where the error is from its byte offset in the text, with the text around it.
+    pub fn at_offset(self, text: &[u8], offset: usize) -> Self {
+        let before = &text[..offset.min(text.len())];
+        let line_start = before.iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1);
+        let line = before.iter().filter(|&&b| b == b'\n').count() + 1;
+        let column = before.len() - line_start + 1;
+        let snippet = Snippet::find(&text[line_start..], 1, column);
+        self.at(line, column, snippet)
+    }
+
+    /// Says where the error is in an input that isn't text, from its byte offset.
+    pub fn at_byte(mut self, offset: usize) -> Self {
+        self.byte_offset = Some(offset);
+        self
+    }
+
+    /// Whether the document is not valid, instead of the input not being readable.
+    pub fn is_invalid(&self) -> bool {
+        self.exit_code == 3
+    }
+
+    /// Says which line of the input the document is in. The line of an error inside the document
+    /// is counted from there.
+    pub fn in_line(mut self, line: usize) -> Self {
+        self.line = Some(line + self.line.unwrap_or(1) - 1);
+        self
+    }
+}
+
+impl std::fmt::Display for DocumentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match (self.line, self.column) {
+            (Some(line), Some(column)) => write!(f, "line {}, column {}: ", line, column)?,
+            (Some(line), None) => write!(f, "line {}: ", line)?,
+            _ => {}
+        }
+        if let Some(offset) = self.byte_offset {
+            write!(f, "offset {}: ", offset)?;
+        }
+        write!(f, "{}", self.message)?;
+        if let Some(snippet) = &self.snippet {
+            write!(f, "\n  {}\n  {}^", snippet.text, " ".repeat(snippet.caret))?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for DocumentError {}
+
+#[cfg(test)]
+mod tests {
+    use super::{DocumentError, Snippet};
+
+    #[test]
+    fn test_display() {
+        let err = serde_json::from_str::<serde_json::Value>("{\"a\": }").unwrap_err();
+        let snippet = Snippet::find(b"{\"a\": }", err.line(), err.column());
+        let err = DocumentError::json(&err)
+            .at(err.line(), err.column(), snippet)
+            .in_line(3);
+
+        assert_eq!(
+            err.to_string(),
+            "line 3, column 7: Invalid JSON: expected value\n  {\"a\": }\n        ^"
+        );
+        assert_eq!(
+            DocumentError::invalid("Invalid TOML: invalid string".to_string())
+                .at_offset(b"a = 1\nb = \n", 10)
+                .to_string(),
+            "line 2, column 5: Invalid TOML: invalid string\n  b = \n      ^"
+        );
+        assert_eq!(
+            DocumentError::invalid("Not valid UTF-8".to_string())
+                .in_line(2)
+                .to_string(),
+            "line 2: Not valid UTF-8"
+        );
+    }
+
+    #[test]
+    fn test_long_line() {
+        let line = format!("{}é{}\n", "a".repeat(100), "b".repeat(100));
+        let snippet = Snippet::new(line.as_bytes(), 103);
+
+        assert_eq!(snippet.caret, 39);
+        assert_eq!(
+            snippet.text,
+            format!("{}é{}", "a".repeat(38), "b".repeat(40))
+        );
+    }
+}
@@ -0,0 +1,336 @@
+use std::io::{self, BufRead, BufReader, Read};
+
+use base64::Engine;
+use serde_json::{Map, Number, Value};
+
+use crate::{
+    errors::document_error::DocumentError,
+    reader::{Content, Document, Source},
+    BytesEncoding,
+};
+
+/// Reads a stream of concatenated CBOR items, and emits them as JSON values, as soon as they are
+/// read. Stops at the first error, or when `emit` returns false.
+pub fn read_cbor<'a>(
+    source: Source<'a>,
+    bytes: BytesEncoding,
+    emit: &mut dyn FnMut(Result<Document<'a>, DocumentError>) -> bool,
+) {
+    read_items(source, "CBOR", emit, &mut |reader| {
+        let value: ciborium::Value = ciborium::from_reader(reader).map_err(|err| match err {
+            ciborium::de::Error::Io(err) => Invalid::io(err),
+            ciborium::de::Error::Syntax(offset) => Invalid::at("syntax error", Some(offset)),
+            ciborium::de::Error::Semantic(offset, reason) => Invalid::at(&reason, offset),
+            ciborium::de::Error::RecursionLimitExceeded => Invalid::at("nested too deep", None),
+        })?;
+        cbor_to_json(value, bytes)
+    });
+}
+
+/// Reads a stream of concatenated MessagePack items, and emits them as JSON values, as soon as
+/// they are read. Stops at the first error, or when `emit` returns false.
+pub fn read_msgpack<'a>(
+    source: Source<'a>,
+    bytes: BytesEncoding,
+    emit: &mut dyn FnMut(Result<Document<'a>, DocumentError>) -> bool,
+) {
+    read_items(source, "MessagePack", emit, &mut |reader| {
+        let value = rmpv::decode::read_value(&mut { reader }).map_err(|err| match err {
+            rmpv::decode::Error::InvalidMarkerRead(err) => Invalid::io(err),
+            rmpv::decode::Error::InvalidDataRead(err) => Invalid::io(err),
+            rmpv::decode::Error::DepthLimitExceeded => Invalid::at("nested too deep", None),
+        })?;
+        Ok(msgpack_to_json(value, bytes))
+    });
+}
+
+// Why an item can't be decoded, and where, from the start of the item, when the decoder says
+struct Invalid {
+    reason: String,
+    offset: Option<usize>,
+}
+
+impl Invalid {
+    fn at(reason: &str, offset: Option<usize>) -> Invalid {
+        Invalid {
+            reason: reason.to_string(),
+            offset,
+        }
+    }
+
+    // The item was cut short, or the input couldn't be read
+    fn io(err: io::Error) -> Invalid {
+        match err.kind() {
+            io::ErrorKind::UnexpectedEof => Invalid::at("unexpected end of input", None),
+            _ => Invalid::at(&err.to_string(), None),
+        }
+    }
+}
+
+/// Counts the bytes read, to say where an item that can't be decoded is.
+struct Counting<R> {
+    reader: R,
+    read: usize,
+}
+
+impl<R: BufRead> Read for Counting<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.reader.read(buf)?;
+        self.read += read;
+        Ok(read)
+    }
+}
+
+impl<R: BufRead> BufRead for Counting<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.reader.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.read += amt;
+        self.reader.consume(amt);
+    }
+}
+
+// Decodes items until the input ends between two of them. The decoders only read the bytes of
+// the item, so the next one starts where the previous one ended. Errors are placed where the
+// decoder says, or where it stopped reading.
+fn read_items<'a>(
+    source: Source<'a>,
+    format: &str,
+    emit: &mut dyn FnMut(Result<Document<'a>, DocumentError>) -> bool,
+    decode: &mut dyn FnMut(&mut dyn BufRead) -> Result<Value, Invalid>,
+) {
+    let reader: Box<dyn BufRead + 'a> = match source {
+        Source::Bytes(bytes) => Box::new(bytes),
+        Source::Reader(reader) => Box::new(BufReader::new(reader)),
+    };
+    let mut reader = Counting { reader, read: 0 };
+
+    for index in 1.. {
+        let start = reader.read;
+        let document = match reader.fill_buf() {
+            Ok([]) => return,
+            Ok(_) => match decode(&mut reader) {
+                Ok(json) => Ok(Document {
+                    index,
+                    content: Content::Value(json),
+                    line: None,
+                    prefix: None,
+                }),
+                Err(invalid) => {
+                    let offset = invalid.offset.map_or(reader.read, |offset| start + offset);
+                    let message = format!("Invalid {}: {}", format, invalid.reason);
+                    Err(DocumentError::invalid(message).at_byte(offset))
+                }
+            },
+            Err(err) => Err(DocumentError::new(
+                format!("Error reading the input: {}", err),
+                1,
+            )),
+        };
+        let failed = document.is_err();
+        if !emit(document) || failed {
+            return;
+        }
+    }
+}
+
+/// Writes binary data as text, since JSON has no byte strings.
+fn encode(bytes: &[u8], encoding: BytesEncoding) -> Value {
+    Value::String(match encoding {
+        BytesEncoding::Base64 => base64::engine::general_purpose::STANDARD.encode(bytes),
+        BytesEncoding::Hex => bytes.iter().map(|b| format!("{:02x}", b)).collect(),
+    })
+}
+
+// Infinity and NaN can't be JSON numbers, so they are kept as strings
+fn float(f: f64) -> Value {
+    match Number::from_f64(f) {
+        Some(number) => Value::Number(number),
+        None => Value::String(f.to_string()),
+    }
+}
+
+// Keys that aren't strings are written as they would be in JSON
+fn key(json: Value) -> String {
+    match json {
+        Value::String(s) => s,
+        json => json.to_string(),
+    }
+}
+
+/// Converts a CBOR value to JSON. Tags are dropped, keeping the value they tag. Fails on the
+/// kinds of values the decoder may add, which have no JSON equivalent yet.
+fn cbor_to_json(value: ciborium::Value, bytes: BytesEncoding) -> Result<Value, Invalid> {
+    let json = match value {
+        ciborium::Value::Null => Value::Null,
+        ciborium::Value::Bool(b) => Value::Bool(b),
+        ciborium::Value::Integer(i) => {
+            // Up to 64 bits plus the sign, which may not fit in an i64 or a u64
+            let i = i128::from(i).to_string();
+            i.parse::<Number>().map_or(Value::String(i), Value::Number)
+        }
+        ciborium::Value::Float(f) => float(f),
+        ciborium::Value::Text(s) => Value::String(s),
+        ciborium::Value::Bytes(b) => encode(&b, bytes),
+        ciborium::Value::Tag(_, value) => cbor_to_json(*value, bytes)?,
+        ciborium::Value::Array(array) => Value::Array(
+            array
+                .into_iter()
+                .map(|v| cbor_to_json(v, bytes))
+                .collect::<Result<_, _>>()?,
+        ),
+        ciborium::Value::Map(map) => {
+            let map = map
+                .into_iter()
+                .map(|(k, v)| Ok((key(cbor_to_json(k, bytes)?), cbor_to_json(v, bytes)?)))
+                .collect::<Result<Map<String, Value>, _>>()?;
+            Value::Object(map)
+        }
+        _ => return Err(Invalid::at("unsupported CBOR value", None)),
+    };
+    Ok(json)
+}
+
+/// Converts a MessagePack value to JSON. Strings that aren't valid UTF-8 and extension types are
+/// written like binary data.
+fn msgpack_to_json(value: rmpv::Value, bytes: BytesEncoding) -> Value {
+    match value {
+        rmpv::Value::Nil => Value::Null,
+        rmpv::Value::Boolean(b) => Value::Bool(b),
+        rmpv::Value::Integer(i) => match (i.as_i64(), i.as_u64()) {
+            (Some(i), _) => Value::Number(i.into()),
+            (_, Some(u)) => Value::Number(u.into()),
+            _ => unreachable!("MessagePack integers are either i64 or u64"),
+        },
+        rmpv::Value::F32(f) => float(f.into()),
+        rmpv::Value::F64(f) => float(f),
+        rmpv::Value::String(s) if s.is_str() => Value::String(s.into_str().unwrap_or_default()),
+        rmpv::Value::String(s) => encode(&s.into_bytes(), bytes),
+        rmpv::Value::Binary(b) | rmpv::Value::Ext(_, b) => encode(&b, bytes),
+        rmpv::Value::Array(array) => Value::Array(
+            array
+                .into_iter()
+                .map(|v| msgpack_to_json(v, bytes))
+                .collect(),
+        ),
+        rmpv::Value::Map(map) => {
+            let map: Map<String, Value> = map
+                .into_iter()
+                .map(|(k, v)| (key(msgpack_to_json(k, bytes)), msgpack_to_json(v, bytes)))
+                .collect();
+            Value::Object(map)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{read_cbor, read_msgpack};
+    use crate::{
+        errors::document_error::DocumentError,
+        reader::{Content, Document, Source},
+        BytesEncoding, InputFormat,
+    };
+
+    fn documents(
+        format: InputFormat,
+        input: &[u8],
+        bytes: BytesEncoding,
+    ) -> Vec<Result<serde_json::Value, String>> {
+        let mut documents = Vec::new();
+        let reader = Box::new(input);
+        let emit = &mut |document: Result<Document, DocumentError>| {
+            documents.push(
+                document
+                    .map_err(|e| e.to_string())
+                    .map(|d| match d.content {
+                        Content::Value(json) => json,
+                        Content::Text(_) => panic!("Binary documents are parsed"),
+                    }),
+            );
+            true
+        };
+        match format {
+            InputFormat::Cbor => read_cbor(Source::Reader(reader), bytes, emit),
+            _ => read_msgpack(Source::Reader(reader), bytes, emit),
+        }
+        documents
+    }
+
+    fn cbor(values: Vec<ciborium::Value>) -> Vec<u8> {
+        let mut data = Vec::new();
+        for value in values {
+            ciborium::into_writer(&value, &mut data).unwrap();
+        }
+        data
+    }
+
+    fn msgpack(values: Vec<rmpv::Value>) -> Vec<u8> {
+        let mut data = Vec::new();
+        for value in values {
+            rmpv::encode::write_value(&mut data, &value).unwrap();
+        }
+        data
+    }
+
+    #[test]
+    fn test_read_cbor() {
+        use ciborium::Value;
+
+        let data = cbor(vec![
+            Value::Map(vec![
+                (Value::Text("id".into()), Value::Bytes(vec![0xde, 0xad])),
+                (Value::Integer(1.into()), Value::Float(0.5)),
+            ]),
+            Value::Array(vec![
+                Value::Tag(1, Box::new(Value::Integer(1700000000.into()))),
+                Value::Integer(u64::MAX.into()),
+                Value::Null,
+            ]),
+        ]);
+
+        assert_eq!(
+            documents(InputFormat::Cbor, &data, BytesEncoding::Base64),
+            vec![
+                Ok(json!({ "id": "3q0=", "1": 0.5 })),
+                Ok(json!([1700000000, u64::MAX, null])),
+            ]
+        );
+        assert_eq!(
+            documents(InputFormat::Cbor, &data[..3], BytesEncoding::Hex),
+            vec![Err("offset 3: Invalid CBOR: unexpected end of input".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_read_msgpack() {
+        use rmpv::Value;
+
+        let data = msgpack(vec![
+            Value::Map(vec![(Value::from("id"), Value::Binary(vec![0xde, 0xad]))]),
+            Value::Array(vec![
+                Value::from(-3),
+                Value::F32(1.5),
+                Value::Ext(1, vec![1]),
+            ]),
+            Value::from(true),
+        ]);
+
+        assert_eq!(
+            documents(InputFormat::Msgpack, &data, BytesEncoding::Hex),
+            vec![
+                Ok(json!({ "id": "dead" })),
+                Ok(json!([-3, 1.5, "01"])),
+                Ok(json!(true)),
+            ]
+        );
+        assert_eq!(
+            documents(InputFormat::Msgpack, &data[..4], BytesEncoding::Hex),
+            vec![Err("offset 4: Invalid MessagePack: unexpected end of input".to_string())]
+        );
+    }
+}
@@ -0,0 +1,120 @@
+use serde_json::Value;
+
+use crate::{
+    errors::document_error::{DocumentError, Snippet},
+    reader::{self, Content, Document, Source},
+    utils::json_utils,
+};
+
+/// Reads a JSON5 file as a single document, and emits it as a JSON value. JSON5 extends the JSON
+/// with comments used by many config files: it allows comments, trailing commas, unquoted keys
+/// and single-quoted strings.
+///
+/// The JSON5 parser turns numbers into floats, losing their digits and the ones too big for them,
+/// so documents that are valid JSON are parsed as JSON instead.
+pub fn read_documents<'a>(
+    source: Source<'a>,
+    emit: &mut dyn FnMut(Result<Document<'a>, DocumentError>) -> bool,
+) {
+    let document = reader::read_text(source).and_then(|text| {
+        let json = match json_utils::parse(&text, None) {
+            Ok(json) => json,
+            Err(_) => json5::from_str::<Value>(&text).map_err(|err| invalid(&text, err))?,
+        };
+        Ok(Document {
+            index: 1,
+            content: Content::Value(json),
+            line: None,
+            prefix: None,
+        })
+    });
+    emit(document);
+}
+
+// The message of the parser shows the text around the error itself, with the reason at the end
+fn invalid(text: &str, err: json5::Error) -> DocumentError {
+    let json5::Error::Message { msg, location } = err;
+    let reason = msg.rsplit_once("= ").map_or(msg.as_str(), |(_, reason)| reason);
+    let error = DocumentError::invalid(format!("Invalid JSON5: {}", reason));
+    match location {
+        Some(location) => {
+            let snippet = Snippet::find(text.as_bytes(), location.line, location.column);
+            error.at(location.line, location.column, snippet)
+        }
+        None => error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use serde_json::json;
+
+    use super::read_documents;
+    use crate::reader::{Content, Source};
+
+    fn documents(input: &str) -> Vec<Result<serde_json::Value, String>> {
+        let mut documents = Vec::new();
+        read_documents(Source::Bytes(input.as_bytes()), &mut |document| {
+            documents.push(
+                document
+                    .map_err(|e| e.to_string())
+                    .map(|d| match d.content {
+                        Content::Value(json) => json,
+                        Content::Text(_) => panic!("JSON5 documents are parsed"),
+                    }),
+            );
+            true
+        });
+        documents
+    }
+
+    #[test]
+    fn test_read_documents() {
+        let input = indoc! {r#"
+            // Compiler options
+            {
+                "compilerOptions": {
+                    /* ES modules */
+                    module: 'esnext',
+                    "strict": true,
+                    "paths": ["src/*", "lib/*",],
+                },
+                "count": 0x10,
+            }
+        "#};
+        assert_eq!(
+            documents(input),
+            vec![Ok(json!({
+                "compilerOptions": {
+                    "module": "esnext",
+                    "strict": true,
+                    "paths": ["src/*", "lib/*"],
+                },
+                "count": 16,
+            }))]
+        );
+    }
+
+    #[test]
+    fn test_json_numbers() {
+        let input = "{\"a\": 1.50, \"b\": 12345678901234567890, \"c\": 1e400}";
+        let json = documents(input).pop().unwrap().unwrap();
+        assert_eq!(
+            json.to_string(),
+            "{\"a\":1.50,\"b\":12345678901234567890,\"c\":1e400}"
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert_eq!(
+            documents("{\"a\": 1 \"b\": 2}"),
+            vec![Err(
+                "line 1, column 7: Invalid JSON5: expected boolean, null, or string\n  \
+                 {\"a\": 1 \"b\": 2}\n        ^"
+                    .to_string()
+            )]
+        );
+    }
+}
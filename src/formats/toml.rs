@@ -0,0 +1,116 @@
+use serde_json::{Map, Number, Value};
+
+use crate::{
+    errors::document_error::DocumentError,
+    reader::{self, Content, Document, Source},
+};
+
+/// Reads a TOML file as a single document, and emits it as a JSON value.
+pub fn read_documents<'a>(
+    source: Source<'a>,
+    emit: &mut dyn FnMut(Result<Document<'a>, DocumentError>) -> bool,
+) {
+    let document = reader::read_text(source).and_then(|text| {
+        let table: toml::Table = text.parse().map_err(|err| invalid(&text, err))?;
+        Ok(Document {
+            index: 1,
+            content: Content::Value(to_json(toml::Value::Table(table))),
+            line: None,
+            prefix: None,
+        })
+    });
+    emit(document);
+}
+
+// The reason can be split in lines, with what was expected in the last one
+fn invalid(text: &str, err: toml::de::Error) -> DocumentError {
+    let reason = err.message().trim().replace('\n', ", ");
+    let error = DocumentError::invalid(format!("Invalid TOML: {}", reason));
+    match err.span() {
+        Some(span) => error.at_offset(text.as_bytes(), span.start),
+        None => error,
+    }
+}
+
+/// Converts a TOML value to JSON. Date-times are kept as strings, as they are written, and so are
+/// the floats JSON can't hold, like `inf` and `nan`.
+fn to_json(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(s) => Value::String(s),
+        toml::Value::Integer(i) => Value::Number(i.into()),
+        toml::Value::Float(f) => match Number::from_f64(f) {
+            Some(number) => Value::Number(number),
+            None => Value::String(f.to_string()),
+        },
+        toml::Value::Boolean(b) => Value::Bool(b),
+        toml::Value::Datetime(datetime) => Value::String(datetime.to_string()),
+        toml::Value::Array(array) => Value::Array(array.into_iter().map(to_json).collect()),
+        toml::Value::Table(table) => {
+            let map: Map<String, Value> = table.into_iter().map(|(k, v)| (k, to_json(v))).collect();
+            Value::Object(map)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use serde_json::json;
+
+    use super::read_documents;
+    use crate::reader::{Content, Source};
+
+    fn documents(input: &str) -> Vec<Result<serde_json::Value, String>> {
+        let mut documents = Vec::new();
+        read_documents(Source::Bytes(input.as_bytes()), &mut |document| {
+            documents.push(
+                document
+                    .map_err(|e| e.to_string())
+                    .map(|d| match d.content {
+                        Content::Value(json) => json,
+                        Content::Text(_) => panic!("TOML documents are parsed"),
+                    }),
+            );
+            true
+        });
+        documents
+    }
+
+    #[test]
+    fn test_read_documents() {
+        let input = indoc! {r#"
+            released = 2024-05-01T10:30:00Z
+            day = 2024-05-01
+
+            [dependencies]
+            serde = { version = "1.0", features = ["derive"] }
+
+            [[bin]]
+            name = "jgrep"
+            ratio = 0.5
+            limit = inf
+        "#};
+        assert_eq!(
+            documents(input),
+            vec![Ok(json!({
+                "released": "2024-05-01T10:30:00Z",
+                "day": "2024-05-01",
+                "dependencies": {
+                    "serde": { "version": "1.0", "features": ["derive"] },
+                },
+                "bin": [{ "name": "jgrep", "ratio": 0.5, "limit": "inf" }],
+            }))]
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert_eq!(
+            documents("a = 1\na = 2\n"),
+            vec![Err(
+                "line 2, column 1: Invalid TOML: duplicate key `a` in document root\n  a = 2\n  ^"
+                    .to_string()
+            )]
+        );
+    }
+}
@@ -0,0 +1,159 @@
+use serde::Deserialize;
+use serde_json::{Map, Number, Value};
+use serde_yaml_ng as yaml;
+
+use crate::{
+    errors::document_error::DocumentError,
+    reader::{self, Content, Document, Source},
+};
+
+/// Reads every document of a YAML stream, separated by `---`, and emits them as JSON values.
+/// Stops at the first error, or when `emit` returns false.
+pub fn read_documents<'a>(
+    source: Source<'a>,
+    emit: &mut dyn FnMut(Result<Document<'a>, DocumentError>) -> bool,
+) {
+    let text = match reader::read_text(source) {
+        Ok(text) => text,
+        Err(err) => {
+            emit(Err(err));
+            return;
+        }
+    };
+
+    for (i, document) in yaml::Deserializer::from_str(&text).enumerate() {
+        let value = yaml::Value::deserialize(document).and_then(|mut value| {
+            value.apply_merge()?;
+            Ok(value)
+        });
+        let document = match value {
+            Ok(value) => Ok(Document {
+                index: i + 1,
+                content: Content::Value(to_json(value)),
+                line: None,
+                prefix: None,
+            }),
+            Err(err) => Err(invalid(&text, err)),
+        };
+        let failed = document.is_err();
+        if !emit(document) || failed {
+            return;
+        }
+    }
+}
+
+// The reason is given without its position, which is shown with the text around it instead
+fn invalid(text: &str, err: yaml::Error) -> DocumentError {
+    let reason = err.to_string();
+    let reason = reason.split(" at line ").next().unwrap_or_default();
+    let error = DocumentError::invalid(format!("Invalid YAML: {}", reason));
+    match err.location() {
+        Some(location) => error.at_offset(text.as_bytes(), location.index()),
+        None => error,
+    }
+}
+
+/// Converts a YAML value to JSON. Keys that aren't strings are written as they would be in JSON,
+/// and tags are dropped, keeping the value they tag.
+fn to_json(value: yaml::Value) -> Value {
+    match value {
+        yaml::Value::Null => Value::Null,
+        yaml::Value::Bool(b) => Value::Bool(b),
+        yaml::Value::Number(n) => number(&n),
+        yaml::Value::String(s) => Value::String(s),
+        yaml::Value::Sequence(seq) => Value::Array(seq.into_iter().map(to_json).collect()),
+        yaml::Value::Mapping(mapping) => {
+            let map: Map<String, Value> = mapping
+                .into_iter()
+                .map(|(k, v)| (key(k), to_json(v)))
+                .collect();
+            Value::Object(map)
+        }
+        yaml::Value::Tagged(tagged) => to_json(tagged.value),
+    }
+}
+
+// The parser only keeps the value of a number, so it is written in its shortest form, and `1.50`
+// becomes `1.5`. Infinity and NaN can't be JSON numbers, so they are kept as strings.
+fn number(n: &yaml::Number) -> Value {
+    let text = n.to_string();
+    match text.parse::<Number>() {
+        Ok(number) => Value::Number(number),
+        Err(_) => Value::String(text),
+    }
+}
+
+fn key(key: yaml::Value) -> String {
+    match to_json(key) {
+        Value::String(s) => s,
+        json => json.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use serde_json::json;
+
+    use super::read_documents;
+    use crate::reader::{Content, Source};
+
+    fn documents(input: &str) -> Vec<Result<serde_json::Value, String>> {
+        let mut documents = Vec::new();
+        read_documents(Source::Bytes(input.as_bytes()), &mut |document| {
+            documents.push(document.map_err(|e| e.to_string()).map(|d| match d.content {
+                Content::Value(json) => json,
+                Content::Text(_) => panic!("YAML documents are parsed"),
+            }));
+            true
+        });
+        documents
+    }
+
+    #[test]
+    fn test_read_documents() {
+        let input = indoc! {"
+            apiVersion: v1
+            metadata: &meta
+              name: web
+              labels: {app: web, 1: true}
+            spec:
+              <<: *meta
+              replicas: 3
+            ---
+            - !tag 1.50
+            - .inf
+            - ~
+        "};
+        assert_eq!(
+            documents(input),
+            vec![
+                Ok(json!({
+                    "apiVersion": "v1",
+                    "metadata": { "name": "web", "labels": { "app": "web", "1": true } },
+                    "spec": {
+                        "name": "web",
+                        "labels": { "app": "web", "1": true },
+                        "replicas": 3,
+                    },
+                })),
+                Ok(json!([1.5, ".inf", null])),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert_eq!(
+            documents("a: 1\n---\na: b: c\n"),
+            vec![
+                Ok(json!({"a": 1})),
+                Err(
+                    "line 3, column 5: Invalid YAML: mapping values are not allowed in this \
+                     context\n  a: b: c\n      ^"
+                        .to_string()
+                ),
+            ]
+        );
+    }
+}
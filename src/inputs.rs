@@ -0,0 +1,203 @@
+use std::path::{Path, PathBuf};
+
+use ignore::WalkBuilder;
+
+use crate::{utils::glob::Glob, InputFormat};
+
+// Archives found in directories are searched for the files of known formats inside them
+const ARCHIVE_EXTENSIONS: [&str; 3] = ["tar", "tgz", "zip"];
+
+/// Where documents are read from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Input {
+    Stdin,
+    File(PathBuf),
+}
+
+impl Input {
+    /// The name printed for the input, before its matches or when listing files.
+    pub fn name(&self) -> String {
+        match self {
+            Input::Stdin => "(standard input)".to_string(),
+            Input::File(path) => path.display().to_string(),
+        }
+    }
+}
+
+/// Finds the inputs to search in the paths given on the command line.
+pub struct Inputs {
+    /// Searches the files inside directories
+    pub recursive: bool,
+    /// When not empty, only the files in directories with a name that matches one of these are
+    /// searched.
+    pub include: Vec<Glob>,
+    /// Files in directories with a name that matches one of these are skipped.
+    pub exclude: Vec<Glob>,
+    /// Skips the files ignored by .gitignore and .ignore files. Hidden files are always skipped.
+    pub ignore: bool,
+}
+
+impl Inputs {
+    /// Calls `on_input` with every input, in order, or with a message for every path that can't
+    /// be searched. `-` is stdin, and no paths at all is stdin too, or the current directory if
+    /// searching recursively.
+    pub fn walk(&self, paths: &[String], on_input: &mut dyn FnMut(Result<Input, String>) -> bool) {
+        if paths.is_empty() {
+            if self.recursive {
+                self.walk_dir(Path::new("."), true, on_input);
+            } else {
+                on_input(Ok(Input::Stdin));
+            }
+            return;
+        }
+
+        for path in paths {
+            let keep_going = if path == "-" {
+                on_input(Ok(Input::Stdin))
+            } else if !Path::new(path).is_dir() {
+                on_input(Ok(Input::File(PathBuf::from(path))))
+            } else if self.recursive {
+                self.walk_dir(Path::new(path), false, on_input)
+            } else {
+                on_input(Err(format!("{}: Is a directory", path)))
+            };
+            if !keep_going {
+                return;
+            }
+        }
+    }
+
+    // The current directory is left out of the names when it wasn't given explicitly, like grep
+    fn walk_dir(
+        &self,
+        dir: &Path,
+        implicit: bool,
+        on_input: &mut dyn FnMut(Result<Input, String>) -> bool,
+    ) -> bool {
+        let walk = WalkBuilder::new(dir)
+            .ignore(self.ignore)
+            .git_ignore(self.ignore)
+            .git_global(self.ignore)
+            .git_exclude(self.ignore)
+            .parents(self.ignore)
+            .require_git(false)
+            .sort_by_file_name(|a, b| a.cmp(b))
+            .build();
+
+        for entry in walk {
+            let input = match entry {
+                Ok(entry) if !entry.file_type().is_some_and(|t| t.is_file()) => continue,
+                Ok(entry) if !self.is_file_included(entry.path()) => continue,
+                Ok(entry) if implicit => {
+                    let path = entry.path().strip_prefix(dir).unwrap_or(entry.path());
+                    Ok(Input::File(path.to_path_buf()))
+                }
+                Ok(entry) => Ok(Input::File(entry.into_path())),
+                Err(err) => Err(err.to_string()),
+            };
+            if !on_input(input) {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn is_included(&self, path: &Path) -> bool {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let included = self.include.is_empty() || self.include.iter().any(|g| g.is_match(&name));
+        included && !self.exclude.iter().any(|g| g.is_match(&name))
+    }
+
+    /// Whether a file found in a directory is searched. Without `--include`, only the files with
+    /// the extension of a known format or of an archive are, because directories usually hold
+    /// other files too.
+    fn is_file_included(&self, path: &Path) -> bool {
+        let name = path.to_string_lossy();
+        let known_format = InputFormat::from_name(&name).is_some() || is_archive(&name);
+        (!self.include.is_empty() || known_format) && self.is_included(path)
+    }
+
+    /// Whether a file inside an archive is searched. Without `--include`, only the files with the
+    /// extension of a known format are, because archives usually hold other files too.
+    pub fn is_member_included(&self, path: &str) -> bool {
+        let known_format = InputFormat::from_name(path).is_some();
+        (!self.include.is_empty() || known_format) && self.is_included(Path::new(path))
+    }
+}
+
+// Looks past the extension of a compressed archive, like `.tar.gz`
+fn is_archive(name: &str) -> bool {
+    let mut extensions = name.rsplit('.');
+    let mut extension = extensions.next().unwrap_or_default();
+    if ["gz", "zst", "bz2", "xz"].contains(&extension) {
+        extension = extensions.next().unwrap_or_default();
+    }
+    name.contains('.') && ARCHIVE_EXTENSIONS.contains(&extension)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::utils::glob::Glob;
+
+    use super::Inputs;
+
+    #[test]
+    fn test_is_included() {
+        let inputs = Inputs {
+            recursive: true,
+            include: vec![Glob::new("*.json"), Glob::new("*.jsonl")],
+            exclude: vec![Glob::new("package*")],
+            ignore: true,
+        };
+
+        assert!(inputs.is_included(Path::new("dir/data.json")));
+        assert!(inputs.is_included(Path::new("logs.jsonl")));
+        assert!(!inputs.is_included(Path::new("dir/package.json")));
+        assert!(!inputs.is_included(Path::new("dir/data.yaml")));
+    }
+
+    #[test]
+    fn test_is_file_included() {
+        let mut inputs = Inputs {
+            recursive: true,
+            include: Vec::new(),
+            exclude: vec![Glob::new("package*")],
+            ignore: true,
+        };
+
+        assert!(inputs.is_file_included(Path::new("dir/data.json")));
+        assert!(inputs.is_file_included(Path::new("logs.ndjson.gz")));
+        assert!(inputs.is_file_included(Path::new("backup.tar.gz")));
+        assert!(inputs.is_file_included(Path::new("export.zip")));
+        assert!(!inputs.is_file_included(Path::new("README.md")));
+        assert!(!inputs.is_file_included(Path::new("src/main.rs")));
+        assert!(!inputs.is_file_included(Path::new("dir/package.json")));
+
+        inputs.include = vec![Glob::new("*.log")];
+        assert!(inputs.is_file_included(Path::new("app.log")));
+        assert!(!inputs.is_file_included(Path::new("dir/data.json")));
+    }
+
+    #[test]
+    fn test_is_member_included() {
+        let mut inputs = Inputs {
+            recursive: false,
+            include: Vec::new(),
+            exclude: vec![Glob::new("package*")],
+            ignore: true,
+        };
+
+        assert!(inputs.is_member_included("dir/data.json"));
+        assert!(inputs.is_member_included("logs.ndjson.gz"));
+        assert!(inputs.is_member_included("k8s/service.yaml"));
+        assert!(inputs.is_member_included("crate/Cargo.toml"));
+        assert!(!inputs.is_member_included("README.md"));
+        assert!(!inputs.is_member_included("dir/package.json"));
+
+        inputs.include = vec![Glob::new("*.md")];
+        assert!(inputs.is_member_included("README.md"));
+        assert!(!inputs.is_member_included("dir/data.json"));
+    }
+}
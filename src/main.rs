@@ -1,21 +1,45 @@
-use std::io::{BufRead, Read};
+use std::fs::File;
+use std::io;
+use std::path::Path;
 use std::process::exit;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
+use archive::Archive;
 use args::Args;
 use clap::ValueEnum;
-use clap::Parser;
+use clap::{CommandFactory, Parser};
+use errors::document_error::DocumentError;
+use inputs::{Input, Inputs};
+use matcher::{Automaton, Nesting};
 use pattern::Pattern;
+use output::{ListFiles, Output};
+use reader::{Content, Document, Source};
+use search::{Found, Search};
+use utils::glob::Glob;
 
+mod archive;
 mod args;
+mod decompress;
+mod inputs;
 mod matcher;
+mod output;
+mod parallel;
 mod pattern;
+mod reader;
+mod search;
+mod streaming;
 
 pub mod utils {
+    pub mod glob;
+    pub mod json_utils;
     pub mod match_utils;
+    pub mod json_events;
     pub mod string_utils;
 }
 
 pub mod printers {
+    pub mod highlight;
+    pub mod invert_printer;
     pub mod json_printer;
     pub mod path_printer;
     pub mod only_printer;
@@ -23,7 +47,15 @@ pub mod printers {
     mod printer_node;
 }
 
+pub mod formats {
+    pub mod binary;
+    pub mod json5;
+    pub mod toml;
+    pub mod yaml;
+}
+
 pub mod errors {
+    pub mod document_error;
     pub mod parsing_error;
 }
 
@@ -34,80 +66,257 @@ enum PrinterType {
     Only,
 }
 
-fn process_complete_json(content: &str, printer: &PrinterType, context: usize, pattern: &Pattern) {
-    let json = serde_json::from_str::<serde_json::Value>(content).unwrap_or_else(|_| {
-        eprintln!("Invalid JSON");
-        exit(3);
-    });
+/// The format of an input.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum InputFormat {
+    Json,
+    /// JSON with comments, trailing commas, unquoted keys and single-quoted strings
+    #[value(alias = "jsonc")]
+    Json5,
+    Yaml,
+    Toml,
+    Cbor,
+    Msgpack,
+}
+
+/// How the byte strings of binary formats are printed, since JSON has none.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum BytesEncoding {
+    Base64,
+    Hex,
+}
 
-    let matches = matcher::match_pattern(&json, pattern);
+// Config files that are usually written with comments, even if they are named like JSON
+const JSONC_NAMES: [&str; 4] = ["tsconfig", "jsconfig", ".eslintrc", "devcontainer"];
+const JSONC_DIRS: [&str; 2] = [".vscode", ".devcontainer"];
 
-    match printer {
-        PrinterType::Path => {
-            printers::path_printer::print(json, matches, context, std::io::stdout())
-        }
-        PrinterType::Json => {
-            printers::json_printer::print(json, matches, context, &mut std::io::stdout())
+impl InputFormat {
+    /// Picks the format from the extension of a file name, looking past the extension of a
+    /// compressed file. JSON config files known to hold comments are read as JSON5.
+    fn from_name(name: &str) -> Option<InputFormat> {
+        let mut extensions = name.rsplit('.');
+        let mut extension = extensions.next()?;
+        if ["gz", "zst", "bz2", "xz"].contains(&extension) {
+            extension = extensions.next()?;
         }
-        PrinterType::Only => {
-            printers::only_printer::print(json, matches, context, std::io::stdout())
+        match extension {
+            "json" if is_jsonc_config(name) => Some(InputFormat::Json5),
+            "json" | "jsonl" | "ndjson" => Some(InputFormat::Json),
+            "jsonc" | "json5" => Some(InputFormat::Json5),
+            "yaml" | "yml" => Some(InputFormat::Yaml),
+            "toml" => Some(InputFormat::Toml),
+            "cbor" => Some(InputFormat::Cbor),
+            "msgpack" | "mpk" => Some(InputFormat::Msgpack),
+            _ => None,
         }
     }
 }
 
-fn process_file(path: &str, printer: PrinterType, context: usize, pattern: &Pattern) {
-    let mut content = String::new();
-    let file = std::fs::File::open(path).unwrap_or_else(|_| {
-        eprintln!("{}: No such file or directory", path);
-        exit(2);
-    });
-    let mut reader = std::io::BufReader::new(file);
-    reader.read_to_string(&mut content).unwrap();
+fn is_jsonc_config(name: &str) -> bool {
+    let path = std::path::Path::new(name);
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let dir = path.parent().and_then(|p| p.file_name()).unwrap_or_default();
+    JSONC_NAMES.iter().any(|n| file_name.starts_with(n))
+        || JSONC_DIRS.iter().any(|d| dir == *d)
+}
+
+/// What matches are counted in, for the match limit and the match count.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum CountScope {
+    /// Every JSON document
+    Document,
+    /// Every input file, or stdin
+    File,
+    /// All the inputs together
+    Total,
+}
 
-    process_complete_json(&content, &printer, context, pattern);
+fn io_error_message(err: &io::Error) -> String {
+    match err.kind() {
+        io::ErrorKind::NotFound => "No such file or directory".to_string(),
+        io::ErrorKind::PermissionDenied => "Permission denied".to_string(),
+        _ => err.to_string(),
+    }
+}
+
+/// What the walk over the inputs sends to the workers, and the workers to the output, in order.
+/// `D` is a document to search, and then the result of searching it.
+enum Item<D> {
+    /// The start of an input, with the name printed before its matches.
+    Begin { name: String, with_filename: bool },
+    Document(D),
+    End,
+    /// An input that can't be read, with the message to report.
+    Failed(String),
 }
 
-fn stream_process(printer: PrinterType, context: usize, pattern: &Pattern) {
-    let stdin = std::io::stdin();
-    let mut buffer = String::new();
+impl<D> Item<D> {
+    fn failed(name: &str, err: &io::Error) -> Self {
+        Item::Failed(format!("{}: {}", name, io_error_message(err)))
+    }
+}
 
-    let mut start = None;
-    let mut depth = 0;
+/// A document to search, with the line it is in with JSON Lines. With --stream, documents are
+/// searched while they are read instead, and only their matches are sent.
+enum Job {
+    Parsed(Result<Document<'static>, DocumentError>, Option<usize>),
+    Searched(Result<Found, DocumentError>),
+}
 
-    for line in stdin.lock().lines() {
-        if line.is_err() {
-            eprintln!("Error reading from stdin");
-            exit(1);
-        }
-        let line = line.unwrap();
-        let mut line = line.trim();
-
-        for (i, c) in line.chars().enumerate() {
-            match (start, c) {
-                (None, '{') | (None, '[') => {
-                    depth = 1;
-                    start = Some(c)
+/// How every input is read and searched.
+struct Reading<'a> {
+    args: &'a Args,
+    inputs: &'a Inputs,
+    search: &'a Search,
+    with_filename: bool,
+    // The number of inputs started, and the last one the output needs no more of, counted from 1
+    begun: AtomicUsize,
+    stopped: AtomicUsize,
+}
+
+impl Reading<'_> {
+    // Sends the documents of an input, or of every file in it if it is an archive. Problems
+    // reading it are sent too. Returns false when nothing else has to be read.
+    fn input(
+        &self,
+        name: &str,
+        stdin: bool,
+        source: Source,
+        emit: &mut dyn FnMut(Item<Job>) -> bool,
+    ) -> bool {
+        // Lines from stdin may come slowly, like from `tail -f`, and would be held back until
+        // there are enough of them to tell whether they are an archive
+        let lines = self.args.ndjson || self.args.extract;
+        let detected = decompress::decompress(source, self.args.decompress).and_then(|source| {
+            match stdin && lines {
+                true => Ok((None, source)),
+                false => Archive::detect(source),
+            }
+        });
+        let (archive, source) = match detected {
+            Ok(detected) => detected,
+            Err(err) => return emit(Item::failed(name, &err)),
+        };
+
+        let Some(archive) = archive else {
+            let format = self.format(name);
+            return self.process(name.to_string(), self.with_filename, source, format, emit);
+        };
+
+        // Files in archives are always labeled, as there is no other way to tell them apart
+        let mut keep_going = true;
+        let include = |path: &str| self.inputs.is_member_included(path);
+        let read = archive.members(source, &include, &mut |member| {
+            let member_name = format!("{}:{}", name, member.path);
+            keep_going = match decompress::decompress(Source::Bytes(&member.content), false) {
+                Ok(source) => {
+                    let format = self.format(&member.path);
+                    self.process(member_name, !self.args.no_filename, source, format, emit)
                 }
-                (None, c) => {
-                    eprintln!("Invalid JSON, must start with '{{' or '[', starts with '{c}'");
-                    exit(3);
+                Err(err) => emit(Item::failed(&member_name, &err)),
+            };
+            keep_going
+        });
+        match read {
+            Ok(()) => keep_going,
+            Err(err) => keep_going && emit(Item::failed(name, &err)),
+        }
+    }
+
+    fn format(&self, name: &str) -> InputFormat {
+        let jsonc = self.args.jsonc.then_some(InputFormat::Json5);
+        self.args
+            .input_format
+            .or(jsonc)
+            .or_else(|| InputFormat::from_name(name))
+            .unwrap_or(InputFormat::Json)
+    }
+
+    // Sends the documents of the input as they are read, until the output needs no more of them.
+    // With --stream, JSON documents are searched while they are parsed instead. With JSON Lines,
+    // the matches and the errors carry the line of their document. Returns false when nothing
+    // else has to be read.
+    fn process(
+        &self,
+        name: String,
+        with_filename: bool,
+        source: Source,
+        format: InputFormat,
+        emit: &mut dyn FnMut(Item<Job>) -> bool,
+    ) -> bool {
+        let number = self.begun.fetch_add(1, Ordering::Relaxed) + 1;
+        if !emit(Item::Begin { name, with_filename }) {
+            return false;
+        }
+
+        let mut keep_going = true;
+        let mut send = |job| {
+            keep_going = emit(Item::Document(job));
+            keep_going && self.stopped.load(Ordering::Relaxed) != number
+        };
+        let numbered = (self.args.ndjson || self.args.extract) && format == InputFormat::Json;
+        let mut send_document = |document: Result<Document, DocumentError>| {
+            let document = document.map(Document::into_owned);
+            let line = document.as_ref().ok().filter(|_| numbered).map(|d| d.index);
+            send(Job::Parsed(document, line))
+        };
+
+        let search = self.search;
+        let emit_document = &mut send_document;
+        match format {
+            InputFormat::Json if self.args.stream => {
+                let send = &mut |found| send(Job::Searched(found));
+                match source {
+                    Source::Reader(reader) => streaming::process(reader, search, send),
+                    Source::Bytes(bytes) => streaming::process(bytes, search, send),
                 }
-                (Some('{'), '{') | (Some('['), '[') => depth += 1,
-                (Some('{'), '}') | (Some('['), ']') => depth -= 1,
-                _ => {}
             }
-
-            if depth == 0 {
-                buffer.push_str(&line[0..=i]);
-                process_complete_json(&buffer, &printer, context, pattern);
-                buffer.clear();
-                start = None;
-                line = &line[i + 1..];
+            InputFormat::Json if self.args.ndjson => reader::read_lines(source, emit_document),
+            InputFormat::Json if self.args.extract => {
+                reader::extract_documents(source, emit_document)
+            }
+            InputFormat::Json => {
+                reader::read_documents(source, self.args.skip_invalid, emit_document)
+            }
+            InputFormat::Json5 => formats::json5::read_documents(source, emit_document),
+            InputFormat::Yaml => formats::yaml::read_documents(source, emit_document),
+            InputFormat::Toml => formats::toml::read_documents(source, emit_document),
+            InputFormat::Cbor => formats::binary::read_cbor(source, self.args.bytes, emit_document),
+            InputFormat::Msgpack => {
+                formats::binary::read_msgpack(source, self.args.bytes, emit_document)
             }
         }
+        keep_going && emit(Item::End)
+    }
 
-        buffer.push_str(line);
-        buffer.push('\n');
+    // Runs on the workers
+    fn search(&self, item: Item<Job>) -> Item<Result<Found, DocumentError>> {
+        let job = match item {
+            Item::Document(job) => job,
+            Item::Begin { name, with_filename } => return Item::Begin { name, with_filename },
+            Item::End => return Item::End,
+            Item::Failed(message) => return Item::Failed(message),
+        };
+        let (document, line) = match job {
+            Job::Searched(found) => return Item::Document(found),
+            Job::Parsed(document, line) => (document, line),
+        };
+
+        let found = document.and_then(|document| {
+            let prefix = document.prefix;
+            let found = match document.content {
+                Content::Text(text) => self.search.search(&text),
+                Content::Value(json) => self.search.search_value(json),
+            };
+            let mut found = found.map_err(|err| match document.line {
+                Some(line) => err.in_line(line),
+                None => err,
+            })?;
+            found.line = line;
+            found.prefix = prefix;
+            Ok(found)
+        });
+        Item::Document(found)
     }
 }
 
@@ -124,14 +333,140 @@ fn main() {
         }
     };
 
-    let context = args.context.unwrap_or(0);
-    let printer = get_printer(&args);
+    let list_files = if args.files_with_matches {
+        Some(ListFiles::WithMatches)
+    } else if args.files_without_match {
+        Some(ListFiles::WithoutMatch)
+    } else {
+        None
+    };
 
-    if let Some(path) = args.path {
-        process_file(&path, printer, context, &pattern);
+    let nesting = if args.outermost {
+        Nesting::Outermost
+    } else if args.innermost {
+        Nesting::Innermost
     } else {
-        stream_process(printer, context, &pattern);
+        Nesting::All
     };
+
+    let printer = get_printer(&args);
+    if args.stream && printer == PrinterType::Json {
+        Args::command()
+            .error(
+                clap::error::ErrorKind::ArgumentConflict,
+                "--stream can't be used with the json printer",
+            )
+            .exit();
+    }
+
+    let search = Search {
+        automaton: Automaton::new(&[pattern])
+            .with_depth_limits(args.min_depth, args.max_depth)
+            .with_nesting(nesting),
+        printer,
+        context: args.context.unwrap_or(0),
+        max_depth: args.max_depth_guard,
+        max_count: if list_files.is_some() {
+            Some(1)
+        } else {
+            args.max_count
+        },
+        count_only: args.count || list_files.is_some(),
+        invert: args.invert,
+        color: colored::control::SHOULD_COLORIZE.should_colorize(),
+        parse_nested: args.parse_nested,
+    };
+    let with_filename = match args.paths.as_slice() {
+        _ if args.with_filename || args.no_filename => args.with_filename,
+        [] => args.recursive,
+        [path] => args.recursive && Path::new(path).is_dir(),
+        _ => true,
+    };
+    let mut output = Output::new(&search, args.count_per, args.max_count, list_files)
+        .skip_invalid(args.ndjson || args.skip_invalid);
+
+    let threads = args.threads.unwrap_or_else(|| {
+        std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+    });
+
+    let inputs = Inputs {
+        recursive: args.recursive,
+        include: args.include.iter().map(|g| Glob::new(g)).collect(),
+        exclude: args.exclude.iter().map(|g| Glob::new(g)).collect(),
+        ignore: !args.no_ignore,
+    };
+    let reading = Reading {
+        args: &args,
+        inputs: &inputs,
+        search: &search,
+        with_filename,
+        begun: AtomicUsize::new(0),
+        stopped: AtomicUsize::new(0),
+    };
+
+    // The inputs are read one after another, and all their documents are searched by the same
+    // workers. Unreadable inputs are skipped with a warning, and make the exit code 2 at the end.
+    let mut failed = false;
+    let mut number = 0;
+    let mut skipping = false;
+    parallel::run_ordered(
+        threads,
+        |emit| {
+            inputs.walk(&args.paths, &mut |input| {
+                let stdin = matches!(input, Ok(Input::Stdin));
+                let mapped;
+                let (name, source) = match input {
+                    Ok(Input::Stdin) => {
+                        (Input::Stdin.name(), Source::Reader(Box::new(io::stdin())))
+                    }
+                    Ok(Input::File(path)) => match File::open(&path) {
+                        Ok(file) => {
+                            mapped = reader::map(&file);
+                            let source = match &mapped {
+                                Some(mapped) => Source::Bytes(mapped),
+                                None => Source::Reader(Box::new(file)),
+                            };
+                            (path.display().to_string(), source)
+                        }
+                        Err(err) => return emit(Item::failed(&path.display().to_string(), &err)),
+                    },
+                    Err(message) => return emit(Item::Failed(message)),
+                };
+                reading.input(&name, stdin, source, emit)
+            });
+        },
+        |item| reading.search(item),
+        |item| {
+            match item {
+                Item::Begin { name, with_filename } => {
+                    number += 1;
+                    skipping = false;
+                    output.begin_input(name, with_filename);
+                }
+                Item::Document(found) if !skipping => {
+                    // The rest of the input is dropped, and isn't read any further
+                    if !output.document(found) {
+                        skipping = true;
+                        reading.stopped.store(number, Ordering::Relaxed);
+                    }
+                }
+                Item::Document(_) => {}
+                Item::End => output.end_input(),
+                Item::Failed(message) => {
+                    eprintln!("{}", message);
+                    failed = true;
+                }
+            }
+            !output.is_done()
+        },
+    );
+    output.end();
+
+    if failed || output.skipped {
+        exit(2);
+    }
 }
 
 // Requires that the printer flags are part of the same Clap::ArgGroup
@@ -1,3 +1,5 @@
+use std::ops::Range;
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum MatchNode {
     Key(MatchKey),
@@ -8,12 +10,16 @@ pub enum MatchNode {
 pub struct MatchKey {
     pub key: String,
     pub highlighted: bool,
+    /// The value was a string holding JSON, parsed to search inside it.
+    pub nested: bool,
 }
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct MatchIndex {
     pub index: usize,
     pub highlighted: bool,
+    /// The value was a string holding JSON, parsed to search inside it.
+    pub nested: bool,
 }
 
 impl MatchNode {
@@ -38,12 +44,42 @@ impl MatchNode {
         }
     }
 
+    pub fn is_nested(&self) -> bool {
+        match self {
+            MatchNode::Key(k) => k.nested,
+            MatchNode::Index(i) => i.nested,
+        }
+    }
+
+    pub fn set_nested(&mut self) {
+        match self {
+            MatchNode::Key(k) => k.nested = true,
+            MatchNode::Index(i) => i.nested = true,
+        }
+    }
+
     pub fn new_key(key: String, highlighted: bool) -> MatchNode {
-        MatchNode::Key(MatchKey { key, highlighted })
+        MatchNode::Key(MatchKey {
+            key,
+            highlighted,
+            nested: false,
+        })
     }
 
     pub fn new_index(index: usize, highlighted: bool) -> MatchNode {
-        MatchNode::Index(MatchIndex { index, highlighted })
+        MatchNode::Index(MatchIndex {
+            index,
+            highlighted,
+            nested: false,
+        })
     }
 }
 
+
+/// The parts of a match to highlight. `keys` has the byte ranges of every key in the path that the
+/// pattern matched, and `value` those of the value, when the pattern has one.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Highlight {
+    pub keys: Vec<Vec<Range<usize>>>,
+    pub value: Vec<Range<usize>>,
+}
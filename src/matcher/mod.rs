@@ -1,125 +1,510 @@
-use match_node::MatchNode;
+use std::ops::Range;
+
+use match_node::{Highlight, MatchNode};
 use serde_json::Value;
 
 use crate::{
     pattern::{pattern_node::PatternNode, Pattern},
-    utils::string_utils::wildcard_match,
+    utils::{glob::Glob, match_utils},
 };
 
 pub mod match_node;
+pub mod stream;
+
+#[cfg(test)]
+mod recursive;
 
-fn match_value(json: &Value, matching_value: &str) -> bool {
+fn match_value(json: &Value, matching_value: &Glob) -> bool {
     match json {
-        Value::Null => wildcard_match("null", matching_value),
-        Value::Bool(b) => wildcard_match(&bool::to_string(b), matching_value),
-        Value::Number(n) => wildcard_match(n.as_str(), matching_value),
-        Value::String(s) => wildcard_match(s, matching_value),
+        Value::Null => matching_value.is_match("null"),
+        Value::Bool(b) => matching_value.is_match(&bool::to_string(b)),
+        Value::Number(n) => matching_value.is_match(n.as_str()),
+        Value::String(s) => matching_value.is_match(s),
         _ => false, // TODO also match objects and arrays?
     }
 }
 
-// The tree is traversed recursively, with two kinds of heads, match heads and start heads.
-// The match head is the head of the matching path, and the start head is the head from which match
-// paths start.
-// IMPORTANT: match order needs to be preserved. Some of the weird dessign decisions taken in this
-// function are for that reason. If you plan on refactoring, or modifying keep that in mind.
-fn match_internal(
-    json: &Value,
-    matching_path: &[PatternNode],
-    matching_val: Option<&String>,
-    path: Vec<MatchNode>,
+fn match_value_ranges(json: &Value, matching_value: &Glob) -> Option<Vec<Range<usize>>> {
+    match json {
+        Value::Null => matching_value.find_match("null"),
+        Value::Bool(b) => matching_value.find_match(&bool::to_string(b)),
+        Value::Number(n) => matching_value.find_match(n.as_str()),
+        Value::String(s) => matching_value.find_match(s),
+        _ => None,
+    }
+}
+
+// The value of a node, if it is one a value pattern can match
+fn scalar(json: &Value) -> Option<&Value> {
+    match json {
+        Value::Array(_) | Value::Object(_) => None,
+        _ => Some(json),
+    }
+}
+
+/// A position inside the path of one of the patterns. A state is active at a node when the first
+/// `position` path nodes of the pattern matched the last `position` steps leading to that node.
+#[derive(Debug)]
+struct State {
+    pattern: usize,
+    position: usize,
+    next: Option<NodeTest>,
+}
+
+/// The path node a state has to match to transition into the next state.
+#[derive(Debug)]
+enum NodeTest {
+    Key(Glob),
+    Index(Option<usize>),
+}
+
+#[derive(Debug)]
+struct CompiledPattern {
+    len: usize,
+    value: Option<Glob>,
     or: bool,
-    start_head: bool,
-) -> Vec<Vec<MatchNode>> {
-    let mut result: Vec<Vec<MatchNode>> = Vec::new();
-
-    // Closure to extend the start head path.
-    // The start head is extended only by the start head path. It requires no condition to be
-    // extended.
-    let extend_start_head = |result: &mut Vec<Vec<MatchNode>>, v: &Value, match_node: MatchNode| {
-        let mut next_path = path.clone();
-        next_path.push(match_node);
-        let head_matches = match_internal(v, matching_path, matching_val, next_path, or, true);
-        result.extend(head_matches);
-    };
+}
 
-    // Closure to extend the match path.
-    // When extending the match path, the matching nodes get their first node removed, as it has
-    // already been matched
-    let extend_match = |result: &mut Vec<Vec<MatchNode>>, v: &Value, match_node: MatchNode| {
-        let next_nodes = &matching_path[1..];
-        let mut next_path = path.clone();
-        next_path.push(match_node);
-        let matches = match_internal(v, next_nodes, matching_val, next_path, or, false);
-        result.extend(matches);
-    };
+/// A step of the path leading to the node currently being visited.
+#[derive(Debug, Clone, Copy)]
+enum Step<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+/// Which of the matches that are nested inside each other are kept.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Nesting {
+    All,
+    /// Drops the matches inside another match
+    Outermost,
+    /// Drops the matches that contain another match
+    Innermost,
+}
+
+/// One or more patterns compiled into a single automaton, with one state per path position.
+///
+/// A single depth-first walk of the JSON advances every pattern at once. Every node is a possible
+/// start of a match, so the start state of every pattern is active at every node, together with the
+/// states the parent node transitioned into.
+#[derive(Debug)]
+pub struct Automaton {
+    patterns: Vec<CompiledPattern>,
+    states: Vec<State>,
+    start_states: Vec<usize>,
+    min_depth: usize,
+    max_depth: Option<usize>,
+    nesting: Nesting,
+}
+
+/// Receives the matches found while walking a document.
+trait MatchSink {
+    /// A match of the path to the current node, starting at the node at `start_depth`.
+    fn add_match(&mut self, path: &[Step], start_depth: usize);
+
+    /// Called after visiting a child of a node at the given depth.
+    fn child_done(&mut self, _depth: usize) {}
+
+    /// Whether the walk can stop, because no more matches are needed. `live` is the depth of the
+    /// shallowest node a match found later can still start at, above the current node.
+    fn is_done(&self, live: usize) -> bool;
+}
+
+/// The path to a match starting at the node at `start_depth`, with the matched nodes highlighted.
+fn match_path(path: &[Step], start_depth: usize) -> Vec<MatchNode> {
+    path.iter()
+        .enumerate()
+        .map(|(i, step)| match step {
+            Step::Key(k) => MatchNode::new_key(k.to_string(), i >= start_depth),
+            Step::Index(index) => MatchNode::new_index(*index, i >= start_depth),
+        })
+        .collect()
+}
+
+// Matches are found in document order, but they have to be returned in the order the original
+// recursive matcher found them. That matcher visited the whole subtree of a node once for every
+// node the match could start from, so the matches inside a child come in two blocks: first the
+// ones starting above the child, and then the ones starting at the child or below it.
+// To reproduce that, every match is stored in the buffer of the depth it starts at (`pending`),
+// and the buffers are merged into the output of their parent (`out`) as the walk goes back up.
+struct Collector {
+    pending: Vec<Vec<Vec<MatchNode>>>,
+    out: Vec<Vec<Vec<MatchNode>>>,
+    limit: Option<usize>,
+    count: usize,
+}
+
+impl Collector {
+    fn buffers(&mut self, depth: usize) {
+        while self.out.len() <= depth {
+            self.out.push(Vec::new());
+            self.pending.push(Vec::new());
+        }
+    }
+
+    // The walk can stop inside a node, with matches in the buffers of its depth and of its parents
+    fn into_matches(self) -> Vec<Vec<MatchNode>> {
+        let buffers = self.out.into_iter().zip(self.pending);
+        let mut matches: Vec<_> = buffers
+            .flat_map(|(out, pending)| out.into_iter().chain(pending))
+            .collect();
+        if let Some(limit) = self.limit {
+            matches.truncate(limit);
+        }
+        matches
+    }
+}
+
+impl MatchSink for Collector {
+    fn add_match(&mut self, path: &[Step], start_depth: usize) {
+        let depth = path.len();
+        self.buffers(depth);
+        let m = match_path(path, start_depth);
+        self.count += 1;
+
+        if start_depth == depth {
+            self.out[depth].push(m);
+        } else {
+            self.pending[start_depth].push(m);
+        }
+    }
 
-    // Two possibilities, either there are things left to match, in which case, they need to be
-    // matched, and both the start and the match head extended, or the matching path is empty, in
-    // which case only values are checked, and the start head extended.
-    if matching_path.is_empty() {
-        if or || matching_val.map(|m| match_value(json, m)).unwrap_or(true) {
-            result.push(path.clone());
+    /// Adds the matches found in the child to the output of the node.
+    fn child_done(&mut self, depth: usize) {
+        self.buffers(depth + 1);
+        let pending = std::mem::take(&mut self.pending[depth]);
+        self.out[depth].extend(pending);
+        let child_out = std::mem::take(&mut self.out[depth + 1]);
+        self.out[depth].extend(child_out);
+    }
+
+    // Once merged, the buffers are in the order `out[0]`, `pending[0]`, `out[1]`, `pending[1]`...
+    // A match found later that starts above the current node is added at the end of `pending` of
+    // its start, and any other one after all the buffers. So the matches up to `pending[live]` are
+    // final.
+    fn is_done(&self, live: usize) -> bool {
+        let Some(limit) = self.limit else {
+            return false;
+        };
+        if self.count < limit {
+            return false;
         }
-        if start_head {
-            match json {
-                Value::Array(vec) => vec.iter().enumerate().for_each(|(v, i)| {
-                    extend_start_head(&mut result, i, MatchNode::new_index(v, false));
-                }),
-                Value::Object(map) => map.iter().for_each(|(k, v)| {
-                    extend_start_head(&mut result, v, MatchNode::new_key(k.to_string(), false));
-                }),
-                _ => {}
+        let buffers = self.out.iter().zip(&self.pending);
+        let buffers = buffers.take(live.saturating_add(1));
+        let final_count: usize = buffers.map(|(out, pending)| out.len() + pending.len()).sum();
+        final_count >= limit
+    }
+}
+
+/// Counts the matches, without building their paths.
+struct Counter {
+    count: usize,
+    limit: Option<usize>,
+}
+
+impl MatchSink for Counter {
+    fn add_match(&mut self, _path: &[Step], _start_depth: usize) {
+        self.count += 1;
+    }
+
+    fn is_done(&self, _live: usize) -> bool {
+        self.limit.map(|limit| self.count >= limit).unwrap_or(false)
+    }
+}
+
+/// A node whose children are being visited.
+struct Frame<'a> {
+    carried: Vec<usize>,
+    children: Children<'a>,
+    // The depth of the shallowest node a match can still start at, for the states carried by this
+    // node and by its parents that can match deeper nodes
+    live: usize,
+}
+
+enum Children<'a> {
+    Array(std::iter::Enumerate<std::slice::Iter<'a, Value>>),
+    Object(serde_json::map::Iter<'a>),
+    None,
+}
+
+impl<'a> Iterator for Children<'a> {
+    type Item = (Step<'a>, &'a Value);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Children::Array(iter) => iter.next().map(|(i, v)| (Step::Index(i), v)),
+            Children::Object(iter) => iter.next().map(|(k, v)| (Step::Key(k), v)),
+            Children::None => None,
+        }
+    }
+}
+
+impl Automaton {
+    pub fn new(patterns: &[Pattern]) -> Automaton {
+        let mut states = Vec::new();
+        let mut start_states = Vec::new();
+        for (i, pattern) in patterns.iter().enumerate() {
+            start_states.push(states.len());
+            for position in 0..=pattern.path.len() {
+                let next = pattern.path.get(position).map(|node| match node {
+                    PatternNode::Key(key) => NodeTest::Key(Glob::new(key)),
+                    PatternNode::Index(index) => NodeTest::Index(*index),
+                });
+                states.push(State {
+                    pattern: i,
+                    position,
+                    next,
+                });
             }
         }
-    } else {
-        let current_node = &matching_path[0];
-        match json {
-            Value::Array(json_array) => {
-                for (i, v) in json_array.iter().enumerate() {
-                    if let PatternNode::Index(index) = current_node {
-                        if Some(i) == *index || index.is_none() {
-                            extend_match(&mut result, v, MatchNode::new_index(i, true));
-                        }
-                    }
-                    if start_head {
-                        extend_start_head(&mut result, v, MatchNode::new_index(i, false));
+
+        let patterns = patterns
+            .iter()
+            .map(|pattern| CompiledPattern {
+                len: pattern.path.len(),
+                value: pattern.value.as_deref().map(Glob::new),
+                or: pattern.or,
+            })
+            .collect();
+
+        Automaton {
+            patterns,
+            states,
+            start_states,
+            min_depth: 0,
+            max_depth: None,
+            nesting: Nesting::All,
+        }
+    }
+
+    /// Only matches paths of at least `min_depth` nodes, and at most `max_depth`. The walk doesn't
+    /// go deeper than `max_depth`.
+    pub fn with_depth_limits(mut self, min_depth: usize, max_depth: Option<usize>) -> Automaton {
+        self.min_depth = min_depth;
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Drops the matches nested inside, or containing, another match, so that every value is
+    /// matched once.
+    pub fn with_nesting(mut self, nesting: Nesting) -> Automaton {
+        self.nesting = nesting;
+        self
+    }
+
+    /// Whether the state matches a node. `scalar` is the value of the node, or `None` for arrays
+    /// and objects, which values never match.
+    fn accepts(&self, state: &State, scalar: Option<&Value>) -> bool {
+        let pattern = &self.patterns[state.pattern];
+        let matching_val = pattern.value.as_ref();
+        let value_matches = |m: &Glob| scalar.is_some_and(|json| match_value(json, m));
+        if state.position == pattern.len {
+            pattern.or || matching_val.map(value_matches).unwrap_or(true)
+        } else {
+            pattern.or && matching_val.map(value_matches).unwrap_or(false)
+        }
+    }
+
+    /// Finds the parts of the keys and of the value that a match matched, to highlight them. The
+    /// highlighted nodes of the path are the ones the pattern matched.
+    pub fn highlight(&self, json: &Value, path: &[MatchNode]) -> Highlight {
+        let value = path.iter().fold(json, |value, node| match node {
+            MatchNode::Key(match_k) => &value[&match_k.key],
+            MatchNode::Index(match_i) => &value[match_i.index],
+        });
+        self.highlight_value(value, path)
+    }
+
+    /// Like `highlight`, with the value at the end of the path instead of the whole document.
+    pub fn highlight_value(&self, value: &Value, path: &[MatchNode]) -> Highlight {
+        let start = path.iter().position(|node| node.is_highlighted()).unwrap_or(path.len());
+        let matched = &path[start..];
+
+        // The whole key, if no pattern can tell which part matched
+        let mut highlight = Highlight {
+            keys: path
+                .iter()
+                .map(|node| match node {
+                    MatchNode::Key(match_k) if match_k.highlighted => {
+                        std::iter::once(0..match_k.key.len()).collect()
                     }
-                }
+                    _ => Vec::new(),
+                })
+                .collect(),
+            value: Vec::new(),
+        };
+
+        for (pattern, &first) in self.patterns.iter().zip(&self.start_states) {
+            if matched.len() > pattern.len {
+                continue;
             }
-            Value::Object(map) => {
-                for (k, v) in map.iter() {
-                    if let PatternNode::Key(matching_key) = current_node {
-                        if wildcard_match(k, matching_key) {
-                            extend_match(&mut result, v, MatchNode::new_key(k.to_string(), true));
-                        }
+            if !self.accepts(&self.states[first + matched.len()], scalar(value)) {
+                continue;
+            }
+
+            let mut keys = Vec::with_capacity(matched.len());
+            for (state, node) in self.states[first..].iter().zip(matched) {
+                let ranges = match (&state.next, node) {
+                    (Some(NodeTest::Key(glob)), MatchNode::Key(match_k)) => {
+                        glob.find_match(&match_k.key)
                     }
-                    if start_head {
-                        extend_start_head(&mut result, v, MatchNode::new_key(k.to_string(), false));
+                    (Some(NodeTest::Index(None)), MatchNode::Index(_)) => Some(Vec::new()),
+                    (Some(NodeTest::Index(Some(i))), MatchNode::Index(match_i)) => {
+                        (*i == match_i.index).then(Vec::new)
                     }
+                    _ => None,
+                };
+                match ranges {
+                    Some(ranges) => keys.push(ranges),
+                    None => break,
+                }
+            }
+            if keys.len() < matched.len() {
+                continue;
+            }
+
+            for (i, ranges) in keys.into_iter().enumerate() {
+                if matched[i].as_key().is_some() {
+                    highlight.keys[start + i] = ranges;
                 }
             }
-            _ => {
-                if or && matching_val.map(|m| match_value(json, m)).unwrap_or(false) {
-                    result.push(path);
+            if let Some(glob) = &pattern.value {
+                highlight.value = match_value_ranges(value, glob).unwrap_or_default();
+            }
+            break;
+        }
+        highlight
+    }
+
+    /// Checks which states accept the node, and prepares the frame to visit its children.
+    fn enter<'a>(
+        &self,
+        json: &'a Value,
+        carried: Vec<usize>,
+        live: usize,
+        path: &[Step],
+        sink: &mut impl MatchSink,
+    ) -> Frame<'a> {
+        let depth = path.len();
+        let at_max_depth = self.max_depth.map(|max| depth >= max).unwrap_or(false);
+
+        // Several patterns can match the same path from the same start node, only keep one.
+        let mut start_depths: Vec<usize> = Vec::new();
+        if depth >= self.min_depth {
+            for &id in self.start_states.iter().chain(&carried) {
+                let state = &self.states[id];
+                let start_depth = depth - state.position;
+                if !start_depths.contains(&start_depth) && self.accepts(state, scalar(json)) {
+                    start_depths.push(start_depth);
+                    sink.add_match(path, start_depth);
                 }
             }
         }
+
+        let children = match json {
+            _ if at_max_depth => Children::None,
+            Value::Array(vec) => Children::Array(vec.iter().enumerate()),
+            Value::Object(map) => Children::Object(map.iter()),
+            _ => Children::None,
+        };
+        let live = carried
+            .iter()
+            .map(|&id| &self.states[id])
+            .filter(|state| state.next.is_some())
+            .map(|state| depth - state.position)
+            .fold(live, usize::min);
+        Frame {
+            carried,
+            children,
+            live,
+        }
+    }
+
+    /// The states a child is visited with, from the states that matched its step.
+    fn transition(&self, carried: &[usize], step: Step) -> Vec<usize> {
+        self.start_states
+            .iter()
+            .chain(carried)
+            .filter(|&&id| match (&self.states[id].next, step) {
+                (Some(NodeTest::Key(matching_key)), Step::Key(k)) => matching_key.is_match(k),
+                (Some(NodeTest::Index(index)), Step::Index(i)) => {
+                    index.is_none() || *index == Some(i)
+                }
+                _ => false,
+            })
+            .map(|&id| id + 1)
+            .collect()
+    }
+
+    /// Walks the document, until there are no more nodes or the sink is done. Uses an explicit
+    /// stack instead of recursion, so deeply nested documents can't overflow the stack.
+    fn walk(&self, json: &Value, sink: &mut impl MatchSink) {
+        let mut path = Vec::new();
+        let mut stack = vec![self.enter(json, Vec::new(), usize::MAX, &path, sink)];
+        while let Some(frame) = stack.last_mut() {
+            if sink.is_done(frame.live) {
+                return;
+            }
+
+            match frame.children.next() {
+                Some((step, child)) => {
+                    let next = self.transition(&frame.carried, step);
+                    path.push(step);
+                    let frame = self.enter(child, next, frame.live, &path, sink);
+                    stack.push(frame);
+                }
+                None => {
+                    stack.pop();
+                    if path.pop().is_some() {
+                        sink.child_done(path.len());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the paths to every match of any of the patterns, in the order they would be printed.
+    /// With a limit, only the first `limit` matches are returned, and the walk stops as soon as
+    /// they are known.
+    pub fn find_matches(&self, json: &Value, limit: Option<usize>) -> Vec<Vec<MatchNode>> {
+        if self.nesting != Nesting::All {
+            // Any match can be nested in one found later, so all of them are needed
+            let matches = self.collect_matches(json, None);
+            let mut matches = match self.nesting {
+                Nesting::Outermost => match_utils::outermost_matches(matches),
+                _ => match_utils::innermost_matches(matches),
+            };
+            matches.truncate(limit.unwrap_or(usize::MAX));
+            return matches;
+        }
+        self.collect_matches(json, limit)
+    }
+
+    fn collect_matches(&self, json: &Value, limit: Option<usize>) -> Vec<Vec<MatchNode>> {
+        let mut collector = Collector {
+            pending: Vec::new(),
+            out: Vec::new(),
+            limit,
+            count: 0,
+        };
+        self.walk(json, &mut collector);
+        collector.into_matches()
+    }
+
+    /// Counts the matches, up to the limit if there is one.
+    pub fn count_matches(&self, json: &Value, limit: Option<usize>) -> usize {
+        if self.nesting != Nesting::All {
+            return self.find_matches(json, limit).len();
+        }
+        let mut counter = Counter { count: 0, limit };
+        self.walk(json, &mut counter);
+        counter.count.min(limit.unwrap_or(usize::MAX))
     }
-    result
 }
 
+#[cfg(test)]
 pub fn match_pattern(json: &Value, pattern: &Pattern) -> Vec<Vec<MatchNode>> {
-    let mut matches = match_internal(
-        json,
-        &pattern.path,
-        pattern.value.as_ref(),
-        vec![],
-        pattern.or,
-        true,
-    );
-    matches.dedup();
-    matches
+    Automaton::new(std::slice::from_ref(pattern)).find_matches(json, None)
 }
 
 #[cfg(test)]
@@ -127,7 +512,7 @@ pub mod tests {
     use serde_json::json;
 
     use crate::{
-        matcher::{match_pattern, MatchNode},
+        matcher::{match_pattern, recursive, Automaton, Collector, MatchNode, Nesting},
         pattern::Pattern,
     };
 
@@ -264,4 +649,217 @@ pub mod tests {
             ]]
         )
     }
+
+    #[test]
+    fn test_multiple_patterns() {
+        let patterns = [
+            Pattern::parse(".b").unwrap(),
+            Pattern::parse(": 1").unwrap(),
+            Pattern::parse(".a").unwrap(),
+        ];
+
+        let json = json!({ "a": 1, "b": { "a": 2 } });
+
+        let result = Automaton::new(&patterns).find_matches(&json, None);
+
+        assert_eq!(
+            result,
+            vec![
+                vec![MatchNode::new_key("a".to_string(), true)],
+                vec![MatchNode::new_key("a".to_string(), false)],
+                vec![MatchNode::new_key("b".to_string(), true)],
+                vec![
+                    MatchNode::new_key("b".to_string(), false),
+                    MatchNode::new_key("a".to_string(), true)
+                ],
+            ]
+        )
+    }
+
+    fn reference_json() -> serde_json::Value {
+        json!({
+            "x": { "a": { "k": 1, "name": "name" }, "y": 2 },
+            "name": "Charlie",
+            "list": [[1, 2, { "a": [3, "x"] }], { "name": { "name": "a.b" } }],
+            "a": { "b": { "a": { "b": null } } },
+        })
+    }
+
+    #[test]
+    fn test_same_order_as_recursive() {
+        let json = reference_json();
+        let patterns = [
+            ".*.*", "name", "a", ".a.b", "a.b", ".[]", ".[][2]", ": 1", ": *", "*", ".*: 2", "[0]",
+            ".list[][]", "x:",
+        ];
+
+        for pattern in patterns {
+            let pattern = Pattern::parse(pattern).unwrap();
+            assert_eq!(
+                recursive::match_pattern(&json, &pattern),
+                match_pattern(&json, &pattern),
+                "{:?}",
+                pattern
+            );
+        }
+    }
+
+    fn bench_json(items: usize) -> serde_json::Value {
+        let items: Vec<_> = (0..items)
+            .map(|i| {
+                json!({
+                    "id": i,
+                    "name": format!("item {i}"),
+                    "active": i % 2 == 0,
+                    "meta": {
+                        "rating": i as f64 / 10.0,
+                        "tags": ["a", "b", "c"],
+                        "author": { "name": "John", "verified": false, "meta": { "id": i } }
+                    }
+                })
+            })
+            .collect();
+        json!({ "items": items })
+    }
+
+    // Run with: cargo test --release bench_automaton -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn bench_automaton() {
+        use std::time::Instant;
+
+        let json = bench_json(20_000);
+        let patterns: Vec<Pattern> = ["name", ".meta.author.name: J*", ": 4*", ".items[].id"]
+            .iter()
+            .map(|p| Pattern::parse(p).unwrap())
+            .collect();
+
+        let start = Instant::now();
+        let recursive_count: usize = patterns
+            .iter()
+            .map(|p| recursive::match_pattern(&json, p).len())
+            .sum();
+        let recursive_time = start.elapsed();
+
+        let automaton = Automaton::new(&patterns);
+        let start = Instant::now();
+        let automaton_count = automaton.find_matches(&json, None).len();
+        let automaton_time = start.elapsed();
+
+        println!("recursive: {recursive_count} matches in {recursive_time:?}");
+        println!("automaton: {automaton_count} matches in {automaton_time:?}");
+    }
+
+    #[test]
+    fn test_first_matches() {
+        let json = reference_json();
+        let patterns = ["name", "a", ".[]", ": *", "*", ".*.*", "a.b", ".list[][]", "[0]"];
+
+        for pattern in patterns {
+            let automaton = Automaton::new(&[Pattern::parse(pattern).unwrap()]);
+            let matches = automaton.find_matches(&json, None);
+            for limit in 0..=matches.len() + 1 {
+                let first = automaton.find_matches(&json, Some(limit));
+                assert_eq!(&matches[..limit.min(matches.len())], first.as_slice());
+            }
+        }
+    }
+
+    #[test]
+    fn test_stops_at_first_matches() {
+        let json = bench_json(1_000);
+        let count = |pattern: &str, limit| {
+            let automaton = Automaton::new(&[Pattern::parse(pattern).unwrap()]);
+            let mut collector = Collector {
+                pending: Vec::new(),
+                out: Vec::new(),
+                limit: Some(limit),
+                count: 0,
+            };
+            automaton.walk(&json, &mut collector);
+            collector.count
+        };
+
+        // Nested matches are final as soon as nothing above them can match anymore
+        assert_eq!(count("name", 1), 1);
+        assert_eq!(count("name", 3), 3);
+        assert_eq!(count(".meta.author", 2), 2);
+        // Matches starting at the root are printed first, so they have to be waited for
+        assert_eq!(count(".items", 1), 1);
+        assert_eq!(count(".items[].id", 1), 1);
+    }
+
+    #[test]
+    fn test_count_matches() {
+        let json = reference_json();
+        let patterns = ["name", "a", ".[]", ": *", "*", ".x"];
+
+        for pattern in patterns {
+            let automaton = Automaton::new(&[Pattern::parse(pattern).unwrap()]);
+            let matches = automaton.find_matches(&json, None);
+            assert_eq!(matches.len(), automaton.count_matches(&json, None));
+            assert_eq!(matches.len().min(2), automaton.count_matches(&json, Some(2)));
+        }
+    }
+
+    #[test]
+    fn test_depth_limits() {
+        let pattern = Pattern::parse(".id").unwrap();
+        let json = json!({ "id": 1, "user": { "id": 2, "payload": { "id": 3 } } });
+
+        let automaton = Automaton::new(&[pattern]).with_depth_limits(2, Some(2));
+        let result = automaton.find_matches(&json, None);
+
+        assert_eq!(
+            result,
+            vec![vec![
+                MatchNode::new_key("user".to_string(), false),
+                MatchNode::new_key("id".to_string(), true)
+            ]]
+        )
+    }
+
+    #[test]
+    fn test_nesting() {
+        let pattern = Pattern::parse(".a*").unwrap();
+        let json = json!({ "a": { "ab": 1 }, "b": { "a": 2 } });
+        let a = MatchNode::new_key("a".to_string(), true);
+        let ab = MatchNode::new_key("ab".to_string(), true);
+        let b = MatchNode::new_key("b".to_string(), false);
+
+        let automaton = Automaton::new(std::slice::from_ref(&pattern));
+        assert_eq!(automaton.find_matches(&json, None).len(), 3);
+
+        let automaton =
+            Automaton::new(std::slice::from_ref(&pattern)).with_nesting(Nesting::Outermost);
+        assert_eq!(
+            automaton.find_matches(&json, None),
+            vec![vec![a.clone()], vec![b.clone(), a.clone()]]
+        );
+
+        let automaton = Automaton::new(&[pattern]).with_nesting(Nesting::Innermost);
+        assert_eq!(
+            automaton.find_matches(&json, None),
+            vec![
+                vec![MatchNode::new_key("a".to_string(), false), ab],
+                vec![b, a]
+            ]
+        );
+        assert_eq!(automaton.count_matches(&json, Some(1)), 1);
+    }
+
+    #[test]
+    #[allow(clippy::single_range_in_vec_init)]
+    fn test_highlight() {
+        let pattern = Pattern::parse(".*name[]: *ips*").unwrap();
+        let json = json!({ "user": { "username": ["lorem ipsum"] } });
+
+        let automaton = Automaton::new(&[pattern]);
+        let matches = automaton.find_matches(&json, None);
+        assert_eq!(matches.len(), 1);
+
+        let highlight = automaton.highlight(&json, &matches[0]);
+        assert_eq!(highlight.keys, vec![vec![], vec![4..8], vec![]]);
+        assert_eq!(highlight.value, vec![6..9]);
+    }
 }
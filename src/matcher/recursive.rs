@@ -0,0 +1,129 @@
+// The original recursive matcher. It is kept as a reference implementation, to check that the
+// automaton in the parent module produces exactly the same matches, in the same order, and to
+// benchmark against it.
+
+use serde_json::Value;
+
+use crate::{
+    matcher::match_node::MatchNode,
+    pattern::{pattern_node::PatternNode, Pattern},
+    utils::glob::Glob,
+};
+
+fn wildcard_match(haystack: &str, needle: &str) -> bool {
+    Glob::new(needle).is_match(haystack)
+}
+
+fn match_value(json: &Value, matching_value: &str) -> bool {
+    match json {
+        Value::Null => wildcard_match("null", matching_value),
+        Value::Bool(b) => wildcard_match(&bool::to_string(b), matching_value),
+        Value::Number(n) => wildcard_match(n.as_str(), matching_value),
+        Value::String(s) => wildcard_match(s, matching_value),
+        _ => false, // TODO also match objects and arrays?
+    }
+}
+
+// The tree is traversed recursively, with two kinds of heads, match heads and start heads.
+// The match head is the head of the matching path, and the start head is the head from which match
+// paths start.
+// IMPORTANT: match order needs to be preserved. Some of the weird dessign decisions taken in this
+// function are for that reason. If you plan on refactoring, or modifying keep that in mind.
+fn match_internal(
+    json: &Value,
+    matching_path: &[PatternNode],
+    matching_val: Option<&String>,
+    path: Vec<MatchNode>,
+    or: bool,
+    start_head: bool,
+) -> Vec<Vec<MatchNode>> {
+    let mut result: Vec<Vec<MatchNode>> = Vec::new();
+
+    // Closure to extend the start head path.
+    // The start head is extended only by the start head path. It requires no condition to be
+    // extended.
+    let extend_start_head = |result: &mut Vec<Vec<MatchNode>>, v: &Value, match_node: MatchNode| {
+        let mut next_path = path.clone();
+        next_path.push(match_node);
+        let head_matches = match_internal(v, matching_path, matching_val, next_path, or, true);
+        result.extend(head_matches);
+    };
+
+    // Closure to extend the match path.
+    // When extending the match path, the matching nodes get their first node removed, as it has
+    // already been matched
+    let extend_match = |result: &mut Vec<Vec<MatchNode>>, v: &Value, match_node: MatchNode| {
+        let next_nodes = &matching_path[1..];
+        let mut next_path = path.clone();
+        next_path.push(match_node);
+        let matches = match_internal(v, next_nodes, matching_val, next_path, or, false);
+        result.extend(matches);
+    };
+
+    // Two possibilities, either there are things left to match, in which case, they need to be
+    // matched, and both the start and the match head extended, or the matching path is empty, in
+    // which case only values are checked, and the start head extended.
+    if matching_path.is_empty() {
+        if or || matching_val.map(|m| match_value(json, m)).unwrap_or(true) {
+            result.push(path.clone());
+        }
+        if start_head {
+            match json {
+                Value::Array(vec) => vec.iter().enumerate().for_each(|(v, i)| {
+                    extend_start_head(&mut result, i, MatchNode::new_index(v, false));
+                }),
+                Value::Object(map) => map.iter().for_each(|(k, v)| {
+                    extend_start_head(&mut result, v, MatchNode::new_key(k.to_string(), false));
+                }),
+                _ => {}
+            }
+        }
+    } else {
+        let current_node = &matching_path[0];
+        match json {
+            Value::Array(json_array) => {
+                for (i, v) in json_array.iter().enumerate() {
+                    if let PatternNode::Index(index) = current_node {
+                        if Some(i) == *index || index.is_none() {
+                            extend_match(&mut result, v, MatchNode::new_index(i, true));
+                        }
+                    }
+                    if start_head {
+                        extend_start_head(&mut result, v, MatchNode::new_index(i, false));
+                    }
+                }
+            }
+            Value::Object(map) => {
+                for (k, v) in map.iter() {
+                    if let PatternNode::Key(matching_key) = current_node {
+                        if wildcard_match(k, matching_key) {
+                            extend_match(&mut result, v, MatchNode::new_key(k.to_string(), true));
+                        }
+                    }
+                    if start_head {
+                        extend_start_head(&mut result, v, MatchNode::new_key(k.to_string(), false));
+                    }
+                }
+            }
+            _ => {
+                if or && matching_val.map(|m| match_value(json, m)).unwrap_or(false) {
+                    result.push(path);
+                }
+            }
+        }
+    }
+    result
+}
+
+pub fn match_pattern(json: &Value, pattern: &Pattern) -> Vec<Vec<MatchNode>> {
+    let mut matches = match_internal(
+        json,
+        &pattern.path,
+        pattern.value.as_ref(),
+        vec![],
+        pattern.or,
+        true,
+    );
+    matches.dedup();
+    matches
+}
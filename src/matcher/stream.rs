@@ -0,0 +1,396 @@
+use std::{collections::VecDeque, io::Read};
+
+use serde_json::{Map, Value};
+
+use crate::{
+    errors::document_error::DocumentError,
+    matcher::{match_node::MatchNode, match_path, Automaton, Step},
+    utils::{
+        json_events::{Event, EventParser},
+        json_utils,
+    },
+};
+
+/// A match found while streaming a document.
+pub struct StreamMatch {
+    pub path: Vec<MatchNode>,
+    /// The matched value, when values are captured.
+    pub value: Option<Value>,
+}
+
+impl Drop for StreamMatch {
+    fn drop(&mut self) {
+        if let Some(value) = self.value.take() {
+            json_utils::drop_deep(value);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum PathStep {
+    Key(String),
+    Index(usize),
+}
+
+impl PathStep {
+    fn as_step(&self) -> Step<'_> {
+        match self {
+            PathStep::Key(k) => Step::Key(k),
+            PathStep::Index(i) => Step::Index(*i),
+        }
+    }
+}
+
+/// An array or object whose children are being parsed.
+struct OpenNode {
+    carried: Vec<usize>,
+    is_object: bool,
+    next_index: usize,
+    key: Option<String>,
+    // Past the maximum depth, children aren't matched
+    skip_children: bool,
+}
+
+/// Builds a matched value from the events of its subtree.
+#[derive(Default)]
+struct Capture {
+    stack: Vec<Partial>,
+    value: Option<Value>,
+}
+
+enum Partial {
+    Array(Vec<Value>),
+    Object(Map<String, Value>, Option<String>),
+}
+
+impl Capture {
+    fn feed(&mut self, event: &Event) {
+        let value = match event {
+            Event::StartArray => return self.stack.push(Partial::Array(Vec::new())),
+            Event::StartObject => return self.stack.push(Partial::Object(Map::new(), None)),
+            Event::Key(k) => {
+                if let Some(Partial::Object(_, key)) = self.stack.last_mut() {
+                    *key = Some(k.clone());
+                }
+                return;
+            }
+            Event::Scalar(value) => value.clone(),
+            Event::EndArray | Event::EndObject => match self.stack.pop() {
+                Some(Partial::Array(vec)) => Value::Array(vec),
+                Some(Partial::Object(map, _)) => Value::Object(map),
+                None => return,
+            },
+        };
+        match self.stack.last_mut() {
+            Some(Partial::Array(vec)) => vec.push(value),
+            Some(Partial::Object(map, key)) => {
+                map.insert(key.take().unwrap_or_default(), value);
+            }
+            None => self.value = Some(value),
+        }
+    }
+}
+
+/// A match waiting to be reported, until its value and the values of the matches before it are
+/// complete.
+struct Queued {
+    path: Vec<MatchNode>,
+    value: Captured,
+}
+
+/// How the value of a queued match is built.
+enum Captured {
+    Nothing,
+    /// From the events of its subtree, which starts at the depth.
+    Whole(Capture, usize),
+    /// Taken from the value of the match it is inside of, at the steps from it. Only one subtree is
+    /// captured at a time, so matches inside matches don't hold a copy each.
+    Within(Vec<PathStep>),
+}
+
+impl Queued {
+    fn is_complete(&self) -> bool {
+        match &self.value {
+            Captured::Whole(capture, _) => capture.value.is_some(),
+            Captured::Nothing | Captured::Within(_) => true,
+        }
+    }
+}
+
+/// The value of the last match with a subtree, kept while the matches inside it are reported.
+struct Held(Value);
+
+impl Held {
+    fn get(&self, steps: &[PathStep]) -> Option<Value> {
+        let mut value = &self.0;
+        for step in steps {
+            value = match step {
+                PathStep::Key(k) => value.get(k)?,
+                PathStep::Index(i) => value.get(i)?,
+            };
+        }
+        Some(value.clone())
+    }
+}
+
+impl Drop for Held {
+    fn drop(&mut self) {
+        json_utils::drop_deep(std::mem::take(&mut self.0));
+    }
+}
+
+impl Automaton {
+    /// Matches a document while it is parsed, starting with its first event, keeping only the path
+    /// to the current node. The subtrees of the matches are only built if `capture` is set.
+    ///
+    /// Matches are reported in the order they are found in the document, as soon as their values
+    /// are complete, until `on_match` returns false. Returns whether the whole document was read.
+    pub fn stream_document<R: Read>(
+        &self,
+        parser: &mut EventParser<R>,
+        first: Event,
+        capture: bool,
+        on_match: &mut dyn FnMut(StreamMatch) -> bool,
+    ) -> Result<bool, DocumentError> {
+        let mut path: Vec<PathStep> = Vec::new();
+        let mut open: Vec<OpenNode> = Vec::new();
+        let mut queue: VecDeque<Queued> = VecDeque::new();
+        let mut held: Option<Held> = None;
+        let mut event = first;
+
+        loop {
+            match &event {
+                Event::Key(k) => {
+                    if let Some(node) = open.last_mut() {
+                        node.key = Some(k.clone());
+                    }
+                }
+                Event::EndObject | Event::EndArray => {
+                    open.pop();
+                    path.pop();
+                }
+                Event::StartObject | Event::StartArray | Event::Scalar(_) => {
+                    self.start_node(&event, &mut path, &mut open, &mut queue, capture);
+                }
+            }
+
+            for queued in queue.iter_mut() {
+                if let Captured::Whole(capture, _) = &mut queued.value {
+                    if capture.value.is_none() {
+                        capture.feed(&event);
+                    }
+                }
+            }
+            // A scalar ends right away
+            if let Event::Scalar(_) = event {
+                if !open.is_empty() {
+                    path.pop();
+                }
+            }
+
+            while queue.front().is_some_and(Queued::is_complete) {
+                let queued = queue.pop_front().unwrap();
+                let value = match queued.value {
+                    Captured::Nothing => None,
+                    Captured::Whole(capture, _) => {
+                        let inside = |q: &Queued| matches!(q.value, Captured::Within(_));
+                        held = match queue.front().is_some_and(inside) {
+                            true => capture.value.clone().map(Held),
+                            false => None,
+                        };
+                        capture.value
+                    }
+                    Captured::Within(steps) => {
+                        let value = held.as_ref().and_then(|held| held.get(&steps));
+                        if !queue.front().is_some_and(|q| matches!(q.value, Captured::Within(_))) {
+                            held = None;
+                        }
+                        value
+                    }
+                };
+                let found = StreamMatch {
+                    path: queued.path,
+                    value,
+                };
+                if !on_match(found) {
+                    return Ok(false);
+                }
+            }
+
+            if open.is_empty() {
+                return Ok(true);
+            }
+            event = parser
+                .next_event()?
+                .ok_or_else(|| DocumentError::invalid("Invalid JSON".to_string()))?;
+        }
+    }
+
+    // Matches a node at its first event, and opens it if it is an array or an object.
+    fn start_node(
+        &self,
+        event: &Event,
+        path: &mut Vec<PathStep>,
+        open: &mut Vec<OpenNode>,
+        queue: &mut VecDeque<Queued>,
+        capture: bool,
+    ) {
+        let (carried, skipped) = match open.last_mut() {
+            None => (Vec::new(), false),
+            Some(parent) => {
+                let step = if parent.is_object {
+                    PathStep::Key(parent.key.take().unwrap_or_default())
+                } else {
+                    parent.next_index += 1;
+                    PathStep::Index(parent.next_index - 1)
+                };
+                let carried = match parent.skip_children {
+                    true => Vec::new(),
+                    false => self.transition(&parent.carried, step.as_step()),
+                };
+                path.push(step);
+                (carried, parent.skip_children)
+            }
+        };
+
+        let depth = path.len();
+        if !skipped && depth >= self.min_depth {
+            let scalar = match event {
+                Event::Scalar(value) => Some(value),
+                _ => None,
+            };
+            let mut steps: Option<Vec<Step>> = None;
+            let mut start_depths: Vec<usize> = Vec::new();
+            for &id in self.start_states.iter().chain(&carried) {
+                let state = &self.states[id];
+                let start_depth = depth - state.position;
+                if !start_depths.contains(&start_depth) && self.accepts(state, scalar) {
+                    start_depths.push(start_depth);
+                    let steps =
+                        steps.get_or_insert_with(|| path.iter().map(PathStep::as_step).collect());
+                    // A subtree being captured holds back the matches after it, so it is the first
+                    let capturing = match queue.front() {
+                        Some(Queued {
+                            value: Captured::Whole(capture, from),
+                            ..
+                        }) if capture.value.is_none() => Some(*from),
+                        _ => None,
+                    };
+                    let value = match (capture, capturing) {
+                        (false, _) => Captured::Nothing,
+                        (true, Some(from)) => Captured::Within(path[from..].to_vec()),
+                        (true, None) => Captured::Whole(Capture::default(), depth),
+                    };
+                    queue.push_back(Queued {
+                        path: match_path(steps, start_depth),
+                        value,
+                    });
+                }
+            }
+        }
+
+        if let Event::StartObject | Event::StartArray = event {
+            let at_max_depth = self.max_depth.is_some_and(|max| depth >= max);
+            open.push(OpenNode {
+                carried,
+                is_object: *event == Event::StartObject,
+                next_index: 0,
+                key: None,
+                skip_children: skipped || at_max_depth,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use crate::{
+        matcher::{match_node::MatchNode, Automaton},
+        pattern::Pattern,
+        utils::json_events::EventParser,
+    };
+
+    fn stream(automaton: &Automaton, input: &str) -> Vec<(Vec<MatchNode>, Option<Value>)> {
+        let mut parser = EventParser::new(input.as_bytes());
+        let mut matches = Vec::new();
+        while let Some(first) = parser.next_event().unwrap() {
+            automaton
+                .stream_document(&mut parser, first, true, &mut |mut m| {
+                    matches.push((std::mem::take(&mut m.path), m.value.take()));
+                    true
+                })
+                .unwrap();
+        }
+        matches
+    }
+
+    #[test]
+    fn test_stream_matches() {
+        let automaton = Automaton::new(&[Pattern::parse(".a").unwrap()]);
+        let matches = stream(
+            &automaton,
+            r#"{"a": {"b": [1, {"a": 2}]}, "c": 3} {"a": null}"#,
+        );
+
+        assert_eq!(
+            matches,
+            vec![
+                (
+                    vec![MatchNode::new_key("a".to_string(), true)],
+                    Some(json!({"b": [1, {"a": 2}]}))
+                ),
+                (
+                    vec![
+                        MatchNode::new_key("a".to_string(), false),
+                        MatchNode::new_key("b".to_string(), false),
+                        MatchNode::new_index(1, false),
+                        MatchNode::new_key("a".to_string(), true),
+                    ],
+                    Some(json!(2))
+                ),
+                (
+                    vec![MatchNode::new_key("a".to_string(), true)],
+                    Some(json!(null))
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_same_matches_as_tree() {
+        let json = json!({
+            "items": [
+                { "id": 1, "name": "a", "tags": ["x", "name"] },
+                { "id": 2, "meta": { "name": { "name": "b" } } },
+            ],
+            "name": "root",
+        });
+        for pattern in [
+            ".name",
+            "name",
+            ": b",
+            "[1]",
+            ".items[].id",
+            "*a*:",
+            ".meta.name",
+        ] {
+            let automaton =
+                Automaton::new(&[Pattern::parse(pattern).unwrap()]).with_depth_limits(1, Some(4));
+            let mut expected = automaton.find_matches(&json, None);
+            let streamed = stream(&automaton, &json.to_string());
+            for (path, value) in &streamed {
+                let expected = path.iter().fold(&json, |value, node| match node {
+                    MatchNode::Key(k) => &value[&k.key],
+                    MatchNode::Index(i) => &value[i.index],
+                });
+                assert_eq!(value.as_ref(), Some(expected), "{}", pattern);
+            }
+            let mut streamed: Vec<_> = streamed.into_iter().map(|(path, _)| path).collect();
+            expected.sort_by_key(|m| format!("{:?}", m));
+            streamed.sort_by_key(|m| format!("{:?}", m));
+            assert_eq!(streamed, expected, "{}", pattern);
+        }
+    }
+}
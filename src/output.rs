@@ -0,0 +1,212 @@
+use std::{
+    io::{self, Write},
+    process::exit,
+};
+
+use crate::{
+    errors::document_error::DocumentError,
+    printers::highlight,
+    search::{Found, Search},
+    CountScope,
+};
+
+/// Lists the names of the inputs, instead of the matches.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ListFiles {
+    WithMatches,
+    WithoutMatch,
+}
+
+/// Writes the results of the searches, in order, keeping track of the match limit and the match
+/// counts.
+pub struct Output<'a> {
+    search: &'a Search,
+    count_per: CountScope,
+    max_count: Option<usize>,
+    list_files: Option<ListFiles>,
+    remaining: Option<usize>,
+    count: usize,
+    // With --invert, whether there is more than one document in the input. The first document is
+    // held back until that is known.
+    stream: bool,
+    first: Option<Found>,
+    // Whether every line of output starts with the name of the input
+    with_filename: bool,
+    name: String,
+    skip_invalid: bool,
+    /// Whether an invalid document was skipped.
+    pub skipped: bool,
+}
+
+impl<'a> Output<'a> {
+    pub fn new(
+        search: &'a Search,
+        count_per: CountScope,
+        max_count: Option<usize>,
+        list_files: Option<ListFiles>,
+    ) -> Self {
+        Output {
+            search,
+            count_per,
+            max_count,
+            list_files,
+            remaining: max_count,
+            count: 0,
+            stream: false,
+            first: None,
+            with_filename: false,
+            name: String::new(),
+            skip_invalid: false,
+            skipped: false,
+        }
+    }
+
+    /// Reports invalid documents and carries on, instead of exiting.
+    pub fn skip_invalid(mut self, skip_invalid: bool) -> Self {
+        self.skip_invalid = skip_invalid;
+        self
+    }
+
+    /// Called before every input file, or stdin. With `with_filename`, every line of output starts
+    /// with the name of the input.
+    pub fn begin_input(&mut self, name: String, with_filename: bool) {
+        self.name = name;
+        self.with_filename = with_filename;
+    }
+
+    /// Whether no more input needs to be read, because the match limit for all of it was reached.
+    pub fn is_done(&self) -> bool {
+        self.count_per == CountScope::Total && self.remaining == Some(0)
+    }
+
+    /// Writes the result of searching a document. Returns false when the input doesn't need to be
+    /// read any further.
+    pub fn document(&mut self, result: Result<Found, DocumentError>) -> bool {
+        let mut found = match result {
+            Ok(found) => found,
+            // Errors always say which input they are in, even when the matches don't
+            Err(err) if self.skip_invalid && err.is_invalid() => {
+                eprintln!("{}: {}", self.name, err);
+                self.skipped = true;
+                return true;
+            }
+            Err(err) => {
+                eprintln!("{}: {}", self.name, err);
+                exit(err.exit_code);
+            }
+        };
+
+        if self.search.invert {
+            self.inverted(found);
+            return true;
+        }
+
+        // The first match is enough to list the input
+        if self.list_files.is_some() {
+            self.count += found.count;
+            return self.count == 0;
+        }
+
+        if self.count_per != CountScope::Document {
+            if let Some(remaining) = self.remaining.as_mut() {
+                found = self.search.truncate(found, *remaining);
+                *remaining -= found.count;
+            }
+        }
+
+        if !self.search.count_only {
+            self.write(&found.output, Some(&found));
+        } else if self.count_per == CountScope::Document {
+            self.write(format!("{}\n", found.count).as_bytes(), Some(&found));
+        } else {
+            self.count += found.count;
+        }
+
+        self.remaining != Some(0)
+    }
+
+    // The documents of lines of text are always printed one per line, even when there is only one
+    fn inverted(&mut self, found: Found) {
+        if !self.stream && found.line.is_none() {
+            let Some(first) = self.first.take() else {
+                self.first = Some(found);
+                return;
+            };
+            self.stream = true;
+            self.write(&self.search.print_inverted(&first, true), Some(&first));
+        }
+        self.write(&self.search.print_inverted(&found, true), Some(&found));
+    }
+
+    /// Writes the output, starting every line with the name of the input, and the line and prefix
+    /// of the document it was found in, if needed.
+    fn write(&self, output: &[u8], found: Option<&Found>) {
+        let mut prefix = String::new();
+        if self.with_filename {
+            match self.search.color {
+                true => prefix.push_str(&highlight::file_name(&self.name)),
+                false => prefix.push_str(&self.name),
+            }
+            prefix.push(':');
+        }
+        if let Some(line) = found.and_then(|f| f.line) {
+            match self.search.color {
+                true => prefix.push_str(&highlight::line_number(line)),
+                false => prefix.push_str(&line.to_string()),
+            }
+            prefix.push(':');
+        }
+        if let Some(text) = found.and_then(|f| f.prefix.as_deref()) {
+            match self.search.color && !text.is_empty() {
+                true => prefix.push_str(&highlight::dim(text)),
+                false => prefix.push_str(text),
+            }
+        }
+
+        let mut stdout = io::stdout().lock();
+        if prefix.is_empty() {
+            stdout.write_all(output).unwrap();
+        } else {
+            for output_line in output.split_inclusive(|&b| b == b'\n') {
+                stdout.write_all(prefix.as_bytes()).unwrap();
+                stdout.write_all(output_line).unwrap();
+            }
+        }
+        stdout.flush().unwrap();
+    }
+
+    /// Called after every input file, or stdin.
+    pub fn end_input(&mut self) {
+        if let Some(list_files) = self.list_files {
+            if (self.count > 0) == (list_files == ListFiles::WithMatches) {
+                if self.search.color {
+                    println!("{}", highlight::file_name(&self.name));
+                } else {
+                    println!("{}", self.name);
+                }
+            }
+            self.count = 0;
+            return;
+        }
+
+        if let Some(first) = self.first.take() {
+            self.write(&self.search.print_inverted(&first, false), Some(&first));
+        }
+        self.stream = false;
+
+        if self.count_per == CountScope::File {
+            if self.search.count_only {
+                self.write(format!("{}\n", self.count).as_bytes(), None);
+            }
+            self.count = 0;
+            self.remaining = self.max_count;
+        }
+    }
+
+    /// Called after all the inputs.
+    pub fn end(&mut self) {
+        if self.count_per == CountScope::Total && self.search.count_only {
+            println!("{}", self.count);
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Mutex,
+    },
+    thread,
+};
+
+/// Runs `work` on every item emitted by `source`, using `threads` worker threads, and passes the
+/// results to `output` in the same order the items were emitted, so the output is deterministic.
+///
+/// `source` runs on its own thread, and stops when `emit` returns false. `output` runs on the
+/// calling thread, and stops everything when it returns false.
+///
+/// With a single thread, no threads are spawned, and every item is processed and output before
+/// the next one is read.
+pub fn run_ordered<T, R, S, F, O>(threads: usize, source: S, work: F, mut output: O)
+where
+    T: Send,
+    R: Send,
+    S: FnOnce(&mut dyn FnMut(T) -> bool) + Send,
+    F: Fn(T) -> R + Sync,
+    O: FnMut(R) -> bool,
+{
+    if threads <= 1 {
+        source(&mut |item| output(work(item)));
+        return;
+    }
+
+    // Limits how many items can be read ahead of the output, so a slow item doesn't make the
+    // results after it pile up in memory.
+    let window = threads * 4;
+
+    let (job_tx, job_rx) = mpsc::sync_channel::<(usize, T)>(threads);
+    let job_rx = Mutex::new(job_rx);
+    let (result_tx, result_rx) = mpsc::channel::<(usize, R)>();
+    let (token_tx, token_rx) = mpsc::sync_channel::<()>(window);
+    for _ in 0..window {
+        token_tx.send(()).unwrap();
+    }
+
+    let stopped = AtomicBool::new(false);
+
+    thread::scope(|scope| {
+        let stopped = &stopped;
+        scope.spawn(move || {
+            let mut index = 0;
+            source(&mut |item| {
+                if token_rx.recv().is_err() || stopped.load(Ordering::Relaxed) {
+                    return false;
+                }
+                let sent = job_tx.send((index, item)).is_ok();
+                index += 1;
+                sent
+            });
+        });
+
+        // The workers keep taking jobs until the source stops, even after the output stopped, so
+        // the source never blocks on a full queue.
+        for _ in 0..threads {
+            let result_tx = result_tx.clone();
+            let job_rx = &job_rx;
+            let work = &work;
+            scope.spawn(move || loop {
+                let job = job_rx.lock().unwrap().recv();
+                let Ok((index, item)) = job else {
+                    break;
+                };
+                if !stopped.load(Ordering::Relaxed) {
+                    let _ = result_tx.send((index, work(item)));
+                }
+            });
+        }
+        drop(result_tx);
+
+        output_in_order(result_rx, token_tx, &mut output);
+        stopped.store(true, Ordering::Relaxed);
+    });
+}
+
+/// Reorders the results as they arrive, until there are no more or the output stops.
+fn output_in_order<R>(
+    result_rx: mpsc::Receiver<(usize, R)>,
+    token_tx: mpsc::SyncSender<()>,
+    output: &mut impl FnMut(R) -> bool,
+) {
+    let mut next = 0;
+    let mut finished = BTreeMap::new();
+    for (index, result) in result_rx.iter() {
+        finished.insert(index, result);
+        while let Some(result) = finished.remove(&next) {
+            if !output(result) {
+                return;
+            }
+            next += 1;
+            // Can't fail while the source is running, there is room for every token
+            let _ = token_tx.send(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::run_ordered;
+
+    #[test]
+    fn test_order() {
+        for threads in [1, 2, 8] {
+            let mut results = Vec::new();
+            run_ordered(
+                threads,
+                |emit| {
+                    for i in 0..1000u64 {
+                        if !emit(i) {
+                            break;
+                        }
+                    }
+                },
+                |i| {
+                    // Make later items finish before earlier ones
+                    std::thread::sleep(std::time::Duration::from_micros((1000 - i) % 7));
+                    i * 2
+                },
+                |r| {
+                    results.push(r);
+                    true
+                },
+            );
+
+            assert_eq!(results, (0..1000).map(|i| i * 2).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn test_stop() {
+        let mut results = Vec::new();
+        run_ordered(
+            4,
+            |emit| {
+                for i in 0.. {
+                    if !emit(i) {
+                        break;
+                    }
+                }
+            },
+            |i| i,
+            |r| {
+                results.push(r);
+                r < 10
+            },
+        );
+
+        assert_eq!(results, (0..=10).collect::<Vec<_>>());
+    }
+}
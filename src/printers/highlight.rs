@@ -0,0 +1,124 @@
+use std::{
+    io::{self, Write},
+    ops::Range,
+};
+
+use colored::Colorize;
+use serde_json::Value;
+
+use crate::{matcher::match_node::MatchNode, utils::json_utils};
+
+/// Paints the byte ranges of the text, like grep paints the matching part of a line.
+pub fn paint(text: &str, ranges: &[Range<usize>]) -> String {
+    paint_with(text, ranges, |s| s.to_string())
+}
+
+/// Dims a part of the path that the pattern didn't match.
+pub fn dim(text: &str) -> String {
+    text.dimmed().to_string()
+}
+
+/// Paints the name of an input file, like grep does.
+pub fn file_name(name: &str) -> String {
+    name.magenta().to_string()
+}
+
+/// Paints the line number of a document, like grep does.
+pub fn line_number(line: usize) -> String {
+    line.to_string().green().to_string()
+}
+
+/// Formats a node of the path as `.key` or `[index]`, painting the matched ranges of the key, or
+/// the whole index. Nodes that the pattern didn't match are dimmed.
+pub fn path_node(node: &MatchNode, key_ranges: &[Range<usize>]) -> String {
+    match node {
+        MatchNode::Key(match_k) if match_k.highlighted => {
+            format!(".{}", paint(&match_k.key, key_ranges))
+        }
+        MatchNode::Index(match_i) if match_i.highlighted => {
+            format!("[{}]", match_i.index.to_string().red())
+        }
+        MatchNode::Key(match_k) => dim(&format!(".{}", match_k.key)),
+        MatchNode::Index(match_i) => dim(&format!("[{}]", match_i.index)),
+    }
+}
+
+/// Quotes and escapes a string like JSON does, painting its byte ranges, which are in the string
+/// itself, without quotes or escapes.
+pub fn quoted(text: &str, ranges: &[Range<usize>]) -> String {
+    format!("\"{}\"", paint_with(text, ranges, escape))
+}
+
+/// Writes the value as compact JSON, painting the byte ranges of a scalar. The ranges of a string
+/// are in the string itself, without quotes or escapes.
+pub fn write_value<W: Write>(
+    writer: &mut W,
+    value: &Value,
+    ranges: &[Range<usize>],
+) -> io::Result<()> {
+    match value {
+        _ if ranges.is_empty() => json_utils::write_compact(writer, value),
+        Value::String(s) => write!(writer, "{}", quoted(s, ranges)),
+        Value::Number(n) => write!(writer, "{}", paint(n.as_str(), ranges)),
+        Value::Bool(_) | Value::Null => write!(writer, "{}", paint(&value.to_string(), ranges)),
+        _ => json_utils::write_compact(writer, value),
+    }
+}
+
+/// Escapes the characters of a string that JSON doesn't allow unescaped.
+fn escape(s: &str) -> String {
+    let quoted = Value::String(s.to_string()).to_string();
+    quoted[1..quoted.len() - 1].to_string()
+}
+
+fn paint_with(text: &str, ranges: &[Range<usize>], format: impl Fn(&str) -> String) -> String {
+    let mut painted = String::new();
+    let mut end = 0;
+    for range in ranges {
+        painted.push_str(&format(&text[end..range.start]));
+        painted.push_str(&format(&text[range.clone()]).red().to_string());
+        end = range.end;
+    }
+    painted.push_str(&format(&text[end..]));
+    painted
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::matcher::match_node::MatchNode;
+
+    use super::{paint, path_node, quoted, write_value};
+
+    #[test]
+    #[allow(clippy::single_range_in_vec_init)]
+    fn test_paint() {
+        colored::control::set_override(true);
+        assert_eq!(
+            paint("username", &[4..6, 7..8]),
+            "user\x1b[31mna\x1b[0mm\x1b[31me\x1b[0m"
+        );
+
+        let mut output = Vec::new();
+        write_value(&mut output, &json!("a\"b"), &[1..2]).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "\"a\x1b[31m\\\"\x1b[0mb\""
+        );
+        assert_eq!(
+            quoted("a\"b\u{1}", &[0..2, 3..4]),
+            "\"\x1b[31ma\\\"\x1b[0mb\x1b[31m\\u0001\x1b[0m\""
+        );
+    }
+
+    #[test]
+    fn test_path_node() {
+        colored::control::set_override(true);
+        let key = MatchNode::new_key("user".to_string(), false);
+        assert_eq!(path_node(&key, &[]), "\x1b[2m.user\x1b[0m");
+
+        let index = MatchNode::new_index(12, true);
+        assert_eq!(path_node(&index, &[]), "[\x1b[31m12\x1b[0m]");
+    }
+}
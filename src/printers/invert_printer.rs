@@ -0,0 +1,88 @@
+use std::io::Write;
+
+use serde_json::Value;
+
+use crate::{matcher::match_node::MatchNode, utils::json_utils};
+
+use super::{json_printer, printer_node::PrinterNode};
+
+/// Prints the JSON with every match removed.
+pub fn print<W: Write>(value: &Value, matches: &[Vec<MatchNode>], writer: &mut W) {
+    // The whole document matched
+    if matches.iter().any(|m| m.is_empty()) {
+        return;
+    }
+
+    let mut printer_node = PrinterNode::new_printed_node_for(value);
+    if let Value::Array(_) | Value::Object(_) = value {
+        printer_node.insert_full(value);
+    }
+    for m in matches {
+        printer_node.remove(m);
+    }
+    printer_node.reindex();
+
+    json_printer::print_node(&printer_node, writer);
+    writeln!(writer).unwrap();
+}
+
+/// Prints the JSON in a single line if it has no matches, to keep only the documents of a stream
+/// that don't match.
+pub fn print_unmatched<W: Write>(value: &Value, matches: &[Vec<MatchNode>], writer: &mut W) {
+    if matches.is_empty() {
+        json_utils::write_compact(writer, value).unwrap();
+        writeln!(writer).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use serde_json::json;
+
+    use crate::matcher::match_node::MatchNode;
+
+    #[test]
+    fn test_invert_printer() {
+        let value = json!({
+            "a": [
+                { "c": 0, "debug": true },
+                { "c": 1 },
+                { "c": 2 },
+            ],
+            "debug": "yes",
+        });
+
+        let matches = vec![
+            vec![
+                MatchNode::new_key("a".to_string(), false),
+                MatchNode::new_index(0, false),
+                MatchNode::new_key("debug".to_string(), true),
+            ],
+            vec![
+                MatchNode::new_key("a".to_string(), true),
+                MatchNode::new_index(1, true),
+            ],
+            vec![MatchNode::new_key("debug".to_string(), true)],
+        ];
+
+        let mut output = Vec::new();
+        super::print(&value, &matches, &mut output);
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            indoc!(r#"
+            {
+              "a": [
+                {
+                  "c": 0
+                },
+                {
+                  "c": 2
+                }
+              ]
+            }
+            "#)
+        );
+    }
+}
@@ -2,24 +2,38 @@ use std::io::Write;
 
 use serde_json::Value;
 
-use crate::matcher::match_node::MatchNode;
-use colored::Colorize;
+use crate::matcher::match_node::{Highlight, MatchNode};
 
-use super::printer_node::PrinterNode;
+use super::{
+    highlight,
+    printer_node::{PrinterNode, Style},
+};
 
-pub fn print<W: Write>(value: Value, mut matches: Vec<Vec<MatchNode>>, context: usize, writer: &mut W) {
+pub fn print<W: Write>(
+    value: &Value,
+    matches: &[Vec<MatchNode>],
+    highlights: Option<&[Highlight]>,
+    context: usize,
+    writer: &mut W,
+) {
     match value {
         Value::Array(_) | Value::Object(_) => {
-            let mut printer_node = PrinterNode::new_printed_node_for(&value);
-            // printer_node.set_highlight(true);
-            for m in matches.iter_mut() {
-                add_matches(&mut printer_node, &value, m.clone(), context);
+            let mut printer_node = PrinterNode::new_printed_node_for(value);
+            for (i, m) in matches.iter().enumerate() {
+                add_matches(&mut printer_node, value, m, highlights.map(|h| &h[i]), context);
             }
             // sort_matches(&mut matches);
-            print_node(None, &printer_node, 0, writer);
+            print_node(&printer_node, writer);
             writeln!(writer).unwrap();
         }
-        _ => panic!("It shouldn't get here. The json value would be invalid."),
+        // A document that is a single scalar can only match as a whole
+        _ => {
+            if !matches.is_empty() {
+                let ranges = highlights.map(|h| h[0].value.as_slice()).unwrap_or_default();
+                highlight::write_value(writer, value, ranges).unwrap();
+                writeln!(writer).unwrap();
+            }
+        }
     }
 }
 
@@ -32,30 +46,42 @@ pub fn print<W: Write>(value: Value, mut matches: Vec<Vec<MatchNode>>, context:
 fn add_matches(
     printer_node: &mut PrinterNode,
     json: &Value,
-    mut m: Vec<MatchNode>,
+    m: &[MatchNode],
+    highlight: Option<&Highlight>,
     context: usize,
 ) {
-    let next_node = m.first();
-    if let Some(next_node) = next_node {
+    let mut printer_node = printer_node;
+    let mut json = json;
+    let path_len = m.len();
+    let mut m = m;
+    while let Some((next_node, rest)) = m.split_first() {
         if m.len() <= context {
             printer_node.insert_full(json);
         }
 
         let next_json = get_value(json, next_node);
         let next_printer_node = printer_node.get_or_insert(next_node, next_json);
-        if next_node.is_highlighted() {
-            next_printer_node.set_highlight(true);
+        if let Some(h) = highlight {
+            if next_node.is_highlighted() {
+                let key_ranges = &h.keys[path_len - m.len()];
+                next_printer_node.set_style(Style::Matched(key_ranges.clone()));
+            } else {
+                next_printer_node.set_style(Style::Dimmed);
+            }
+            if rest.is_empty() && !h.value.is_empty() {
+                next_printer_node.paint_value(next_json, &h.value);
+            }
         }
         match next_printer_node {
             PrinterNode::Array { .. } | PrinterNode::Object { .. } => {
-                m.remove(0);
-                add_matches(next_printer_node, next_json, m, context);
+                printer_node = next_printer_node;
+                json = next_json;
+                m = rest;
             }
-            _ => {}
+            _ => return,
         }
-    } else {
-        printer_node.insert_full(json);
     }
+    printer_node.insert_full(json);
 }
 
 fn get_value<'a>(value: &'a Value, path_node: &MatchNode) -> &'a Value {
@@ -66,78 +92,94 @@ fn get_value<'a>(value: &'a Value, path_node: &MatchNode) -> &'a Value {
     }
 }
 
-fn print_node<W: Write>(
-    node_title: Option<&str>,
-    printer_node: &PrinterNode,
-    indentation: usize,
-    writer: &mut W,
-) {
-    let indent_str = "  ".repeat(indentation);
-    match printer_node {
-        PrinterNode::Array { vec, highlighted } => {
-            let title = if let Some(node_title) = node_title {
-                format!(r#"{}"{}": ["#, indent_str, node_title)
-            } else {
-                format!("{}[", indent_str)
-            };
+/// What is left to print. The nodes are printed with an explicit stack, instead of recursion.
+enum Print<'a> {
+    Node {
+        title: Option<&'a str>,
+        node: &'a PrinterNode,
+        indentation: usize,
+    },
+    Str(&'static str),
+    String(String),
+}
 
-            let (formatted_title, formatted_end) = if *highlighted {
-                (title.red(), format!("{}]", indent_str).red())
-            } else {
-                (title.normal(), format!("{}]", indent_str).normal())
-            };
-
-            writeln!(writer, "{}", formatted_title).unwrap();
-            for (&i, node) in vec.iter() {
-                print_node(None, node, indentation + 1, writer);
-                if i == vec.len() - 1 {
-                    writeln!(writer).unwrap();
-                } else {
-                    writeln!(writer, ",").unwrap();
+pub(super) fn print_node<W: Write>(printer_node: &PrinterNode, writer: &mut W) {
+    let mut stack = vec![Print::Node {
+        title: None,
+        node: printer_node,
+        indentation: 0,
+    }];
+
+    while let Some(next) = stack.pop() {
+        let (node_title, printer_node, indentation) = match next {
+            Print::Node {
+                title,
+                node,
+                indentation,
+            } => (title, node, indentation),
+            Print::Str(s) => {
+                write!(writer, "{}", s).unwrap();
+                continue;
+            }
+            Print::String(s) => {
+                write!(writer, "{}", s).unwrap();
+                continue;
+            }
+        };
+
+        let indent_str = "  ".repeat(indentation);
+        match printer_node {
+            PrinterNode::Array { vec, style } => {
+                writeln!(writer, "{}{}[", indent_str, title(node_title, style)).unwrap();
+                stack.push(Print::String(format!("{}]", indent_str)));
+                for (&i, node) in vec.iter().rev() {
+                    if i == vec.len() - 1 {
+                        stack.push(Print::Str("\n"));
+                    } else {
+                        stack.push(Print::Str(",\n"));
+                    }
+                    stack.push(Print::Node {
+                        title: None,
+                        node,
+                        indentation: indentation + 1,
+                    });
                 }
             }
-            write!(writer, "{}", formatted_end).unwrap();
-        }
-        PrinterNode::Object { map, highlighted } => {
-            let title = if let Some(node_title) = node_title {
-                format!(r#"{}"{}": {{"#, indent_str, node_title)
-            } else {
-                format!("{}{{", indent_str)
-            };
-
-            let (formatted_title, formatted_end) = if *highlighted {
-                (title.red(), format!("{}}}", indent_str).red())
-            } else {
-                (title.normal(), format!("{}}}", indent_str).normal())
-            };
-
-            writeln!(writer, "{}", formatted_title).unwrap();
-            for (i, (k, node)) in map.iter().enumerate() {
-                print_node(Some(k), node, indentation + 1, writer);
-                if i < map.len() - 1 {
-                    writeln!(writer, ",").unwrap();
-                } else {
-                    writeln!(writer).unwrap();
+            PrinterNode::Object { map, style } => {
+                writeln!(writer, "{}{}{{", indent_str, title(node_title, style)).unwrap();
+                stack.push(Print::String(format!("{}}}", indent_str)));
+                for (i, (k, node)) in map.iter().enumerate().rev() {
+                    if i < map.len() - 1 {
+                        stack.push(Print::Str(",\n"));
+                    } else {
+                        stack.push(Print::Str("\n"));
+                    }
+                    stack.push(Print::Node {
+                        title: Some(k),
+                        node,
+                        indentation: indentation + 1,
+                    });
                 }
             }
-            write!(writer, "{}", formatted_end).unwrap();
-        }
-        PrinterNode::Value { val, highlighted } => {
-            let title = if let Some(node_title) = node_title {
-                format!(r#"{}"{}": "#, indent_str, node_title)
-            } else {
-                indent_str
-            };
-
-            if *highlighted {
-                write!(writer, "{}{}", title.red(), val).unwrap();
-            } else {
-                write!(writer, "{}{}", title.normal(), val).unwrap();
+            PrinterNode::Value { val, style } => {
+                write!(writer, "{}{}{}", indent_str, title(node_title, style), val).unwrap();
             }
         }
     }
 }
 
+/// Formats the key of an object member, and the colon after it.
+fn title(node_title: Option<&str>, style: &Style) -> String {
+    let Some(node_title) = node_title else {
+        return String::new();
+    };
+    match style {
+        Style::Plain => format!("{}: ", highlight::quoted(node_title, &[])),
+        Style::Dimmed => format!("{}: ", highlight::dim(&highlight::quoted(node_title, &[]))),
+        Style::Matched(ranges) => format!("{}: ", highlight::quoted(node_title, ranges)),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use serde_json::json;
@@ -173,7 +215,7 @@ mod tests {
         ];
 
         let mut output = Vec::new();
-        json_printer::print(json, matches, 0, &mut output);
+        json_printer::print(&json, &matches, None, 0, &mut output);
 
         assert_eq!(
             String::from_utf8(output).unwrap(),
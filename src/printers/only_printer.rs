@@ -2,14 +2,24 @@ use std::{cmp::min, io::Write};
 
 use serde_json::Value;
 
-use crate::matcher::match_node::MatchNode;
+use crate::{
+    matcher::match_node::{Highlight, MatchNode},
+    printers::highlight,
+};
 
 
-pub fn print<W: Write>(value: Value, matches: Vec<Vec<MatchNode>>, context: usize, mut writer: W) {
-    for path in matches {
-        let mut value_to_print = &value;
+pub fn print<W: Write>(
+    value: &Value,
+    matches: &[Vec<MatchNode>],
+    highlights: Option<&[Highlight]>,
+    context: usize,
+    mut writer: W,
+) {
+    for (n, path) in matches.iter().enumerate() {
+        let mut value_to_print = value;
         let mut path = path.clone();
-        path.truncate(path.len() - min(path.len() - 1, context));
+        let in_context = min(path.len().saturating_sub(1), context);
+        path.truncate(path.len() - in_context);
 
         for node in path {
             match node {
@@ -23,7 +33,11 @@ pub fn print<W: Write>(value: Value, matches: Vec<Vec<MatchNode>>, context: usiz
                 },
             }
         }
-        write!(writer, "{}", value_to_print).unwrap();
+        let value_ranges = match highlights {
+            Some(h) if in_context == 0 => h[n].value.as_slice(),
+            _ => &[],
+        };
+        highlight::write_value(&mut writer, value_to_print, value_ranges).unwrap();
         writeln!(writer).unwrap();
     }
 }
@@ -57,7 +71,7 @@ mod tests {
         ];
 
         let mut output = Vec::new();
-        super::print(value, matches, 0, &mut output);
+        super::print(&value, &matches, None, 0, &mut output);
         let output = String::from_utf8(output).unwrap();
 
         assert_eq!(output, "0\n{\"patatas\":\"felices\"}\n")
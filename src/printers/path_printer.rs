@@ -2,36 +2,78 @@ use std::{cmp::min, io::Write};
 
 use serde_json::Value;
 
-use crate::matcher::match_node::MatchNode;
+use crate::{
+    matcher::match_node::{Highlight, MatchNode},
+    printers::highlight,
+};
 
-pub fn print<W: Write>(value: Value, matches: Vec<Vec<MatchNode>>, context: usize, mut writer: W) {
-    for path in matches {
-        let mut value_to_print = &value;
+// Marks the nodes whose value is JSON parsed from a string
+const NESTED: &str = "<json>";
+
+/// Prints the path and value of every match. With highlights, the matching parts are painted.
+pub fn print<W: Write>(
+    value: &Value,
+    matches: &[Vec<MatchNode>],
+    highlights: Option<&[Highlight]>,
+    context: usize,
+    mut writer: W,
+) {
+    for (i, path) in matches.iter().enumerate() {
+        let highlight = highlights.map(|h| &h[i]);
+        let mut value_to_print = value;
         let mut path = path.clone();
-        path.truncate(path.len() - min(path.len() - 1, context));
+        let in_context = min(path.len().saturating_sub(1), context);
+        path.truncate(path.len() - in_context);
 
-        for node in path {
+        for node in &path {
             match node {
-                MatchNode::Key(match_k) => { 
-                    let k = match_k.key;
-                    value_to_print = &value_to_print[&k];
-                    write!(writer, ".{}", k).unwrap() 
-                },
-                MatchNode::Index(match_i) => { 
-                    let i = match_i.index;
-                    value_to_print = &value_to_print[i];
-                    write!(writer, "[{}]", i).unwrap() 
-                },
+                MatchNode::Key(match_k) => value_to_print = &value_to_print[&match_k.key],
+                MatchNode::Index(match_i) => value_to_print = &value_to_print[match_i.index],
+            }
+        }
+        print_match(&path, value_to_print, highlight, in_context == 0, &mut writer);
+    }
+}
+
+/// Prints the path of a match and the value at the end of it. The value is only highlighted if it
+/// is the matched value, and not a parent of it shown for context.
+pub fn print_match<W: Write>(
+    path: &[MatchNode],
+    value: &Value,
+    highlight: Option<&Highlight>,
+    matched_value: bool,
+    mut writer: W,
+) {
+    for (j, node) in path.iter().enumerate() {
+        match (highlight, node) {
+            (Some(h), _) => write!(writer, "{}", highlight::path_node(node, &h.keys[j])),
+            (None, MatchNode::Key(match_k)) => write!(writer, ".{}", match_k.key),
+            (None, MatchNode::Index(match_i)) => write!(writer, "[{}]", match_i.index),
+        }
+        .unwrap();
+        if node.is_nested() {
+            match highlight {
+                Some(_) => write!(writer, "{}", highlight::dim(NESTED)),
+                None => write!(writer, "{}", NESTED),
             }
+            .unwrap();
         }
-        write!(writer, ": {}", value_to_print).unwrap();
-        writeln!(writer).unwrap();
     }
+    if path.is_empty() {
+        write!(writer, ".").unwrap();
+    }
+    write!(writer, ": ").unwrap();
+    let value_ranges = match highlight {
+        Some(h) if matched_value => h.value.as_slice(),
+        _ => &[],
+    };
+    highlight::write_value(&mut writer, value, value_ranges).unwrap();
+    writeln!(writer).unwrap();
 }
 
 #[cfg(test)]
 mod test {
-    use crate::matcher::match_node::MatchNode;
+    use crate::matcher::match_node::{Highlight, MatchNode};
 
     #[test]
     fn test_path_printer() {
@@ -58,9 +100,33 @@ mod test {
         ];
 
         let mut output = Vec::new();
-        super::print(value, matches, 0, &mut output);
+        super::print(&value, &matches, None, 0, &mut output);
         let output = String::from_utf8(output).unwrap();
 
         assert_eq!(output, ".a[0].c: 0\n.a[3][0]: {\"patatas\":\"felices\"}\n")
     }
+
+    #[test]
+    #[allow(clippy::single_range_in_vec_init)]
+    fn test_highlight() {
+        let value = serde_json::json!({ "user": { "name": "Lorem" } });
+        let matches = vec![vec![
+            MatchNode::new_key("user".to_string(), false),
+            MatchNode::new_key("name".to_string(), true),
+        ]];
+        let highlights = [Highlight {
+            keys: vec![vec![], vec![0..2]],
+            value: vec![1..3],
+        }];
+
+        colored::control::set_override(true);
+        let mut output = Vec::new();
+        super::print(&value, &matches, Some(&highlights), 0, &mut output);
+        let output = String::from_utf8(output).unwrap();
+
+        assert_eq!(
+            output,
+            "\x1b[2m.user\x1b[0m.\x1b[31mna\x1b[0mme: \"L\x1b[31mor\x1b[0mem\"\n"
+        )
+    }
 }
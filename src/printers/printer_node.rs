@@ -1,21 +1,36 @@
+use std::ops::Range;
+
 use indexmap::IndexMap;
 use serde_json::Value;
 
 use crate::matcher::match_node::MatchNode;
 
+use super::highlight;
+
+/// How the key of a node is printed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum Style {
+    #[default]
+    Plain,
+    /// On the path to a match, but not matched by the pattern
+    Dimmed,
+    /// Matched by the pattern, with the byte ranges of the key that matched
+    Matched(Vec<Range<usize>>),
+}
+
 #[derive(Debug)]
 pub enum PrinterNode {
         Array {
         vec: IndexMap<usize, PrinterNode>,
-        highlighted: bool,
+        style: Style,
     },
         Object {
         map: IndexMap<String, PrinterNode>,
-        highlighted: bool,
+        style: Style,
     },
         Value {
         val: String,
-        highlighted: bool,
+        style: Style,
     },
 }
 
@@ -24,27 +39,27 @@ impl PrinterNode {
         match value {
             Value::Array(_) => PrinterNode::Array {
                 vec: IndexMap::new(),
-                highlighted: false,
+                style: Style::Plain,
             },
             Value::Object(_) => PrinterNode::Object {
                 map: IndexMap::new(),
-                highlighted: false,
+                style: Style::Plain,
             },
-            Value::String(s) => PrinterNode::Value {
-                val: format!("\"{}\"", s),
-                highlighted: false,
+            Value::String(_) => PrinterNode::Value {
+                val: value.to_string(),
+                style: Style::Plain,
             },
             Value::Number(n) => PrinterNode::Value {
                 val: n.to_string(),
-                highlighted: false,
+                style: Style::Plain,
             },
             Value::Bool(b) => PrinterNode::Value {
                 val: b.to_string(),
-                highlighted: false,
+                style: Style::Plain,
             },
             Value::Null => PrinterNode::Value {
                 val: "null".to_string(),
-                highlighted: false,
+                style: Style::Plain,
             },
         }
     }
@@ -53,7 +68,7 @@ impl PrinterNode {
         match self {
             PrinterNode::Array {
                 vec,
-                highlighted: _,
+                style: _,
             } => {
                 let match_i = path_node.as_index().unwrap();
                 vec.entry(match_i.index)
@@ -61,7 +76,7 @@ impl PrinterNode {
             }
             PrinterNode::Object {
                 map,
-                highlighted: _,
+                style: _,
             } => {
                 let match_k = path_node.as_key().unwrap();
                 map.entry(match_k.key.clone())
@@ -69,52 +84,122 @@ impl PrinterNode {
             }
             PrinterNode::Value {
                 val: _,
-                highlighted: _,
+                style: _,
             } => panic!("Cannot insert into a value"),
         }
     }
 
+    /// Inserts the whole value under this node. Uses an explicit stack instead of recursion, so
+    /// deeply nested values can't overflow the stack.
     pub fn insert_full(&mut self, value: &Value) {
-        match self {
-            PrinterNode::Array { vec, .. } => {
-                for (i, v) in value.as_array().unwrap().iter().enumerate() {
-                    let mut next_printed_node = Self::new_printed_node_for(v);
-                    match v {
-                        Value::Array(_) | Value::Object(_) => next_printed_node.insert_full(v),
-                        _ => {}
+        let mut stack = vec![(self, value)];
+        while let Some((node, value)) = stack.pop() {
+            match node {
+                PrinterNode::Array { vec, .. } => {
+                    for (i, v) in value.as_array().unwrap().iter().enumerate() {
+                        vec.insert(i, Self::new_printed_node_for(v));
                     }
-                    vec.insert(i, next_printed_node);
+                    let children = vec.iter_mut().map(|(&i, node)| (node, &value[i]));
+                    stack.extend(children.filter(|(_, v)| v.is_array() || v.is_object()));
                 }
-            }
-            PrinterNode::Object { map, .. } => {
-                for (k, v) in value.as_object().unwrap() {
-                    let mut next_printed_node = Self::new_printed_node_for(v);
-                    match v {
-                        Value::Array(_) | Value::Object(_) => next_printed_node.insert_full(v),
-                        _ => {}
+                PrinterNode::Object { map, .. } => {
+                    for (k, v) in value.as_object().unwrap() {
+                        map.insert(k.clone(), Self::new_printed_node_for(v));
                     }
-                    map.insert(k.clone(), next_printed_node);
+                    let children = map.iter_mut().map(|(k, node)| (node, &value[k]));
+                    stack.extend(children.filter(|(_, v)| v.is_array() || v.is_object()));
                 }
+                PrinterNode::Value { .. } => panic!("Cannot insert into a value"),
             }
-            PrinterNode::Value { .. } => panic!("Cannot insert into a value"),
         }
     }
 
-    pub fn set_highlight(&mut self, h: bool) {
-        match self {
-            PrinterNode::Array {
-                vec: _,
-                highlighted,
-            } => *highlighted = h,
-            PrinterNode::Object {
-                map: _,
-                highlighted,
-            } => *highlighted = h,
-            PrinterNode::Value {
-                val: _,
-                highlighted,
-            } => *highlighted = h,
+    /// Removes the node at the end of the path, if it is there.
+    pub fn remove(&mut self, path: &[MatchNode]) {
+        let Some((last, parents)) = path.split_last() else {
+            return;
+        };
+
+        let mut node = self;
+        for path_node in parents {
+            let next = match (node, path_node) {
+                (PrinterNode::Array { vec, .. }, MatchNode::Index(match_i)) => {
+                    vec.get_mut(&match_i.index)
+                }
+                (PrinterNode::Object { map, .. }, MatchNode::Key(match_k)) => {
+                    map.get_mut(&match_k.key)
+                }
+                _ => None,
+            };
+            match next {
+                Some(next) => node = next,
+                None => return,
+            }
+        }
+
+        match (node, last) {
+            (PrinterNode::Array { vec, .. }, MatchNode::Index(match_i)) => {
+                vec.shift_remove(&match_i.index);
+            }
+            (PrinterNode::Object { map, .. }, MatchNode::Key(match_k)) => {
+                map.shift_remove(&match_k.key);
+            }
+            _ => {}
+        }
+    }
+
+    /// Numbers the elements of every array from 0 again, after some were removed.
+    pub fn reindex(&mut self) {
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            match node {
+                PrinterNode::Array { vec, .. } => {
+                    let elements = std::mem::take(vec);
+                    *vec = elements.into_values().enumerate().collect();
+                    stack.extend(vec.values_mut());
+                }
+                PrinterNode::Object { map, .. } => stack.extend(map.values_mut()),
+                PrinterNode::Value { .. } => {}
+            }
+        }
+    }
+
+    /// Sets how the node is printed. A node on the path to several matches is printed as matched
+    /// if any of them matched it.
+    pub fn set_style(&mut self, new_style: Style) {
+        let (PrinterNode::Array { style, .. }
+        | PrinterNode::Object { style, .. }
+        | PrinterNode::Value { style, .. }) = self;
+        if !matches!(style, Style::Matched(_)) && new_style != Style::Plain {
+            *style = new_style;
+        }
+    }
+
+    /// Paints the matched byte ranges of a scalar value.
+    pub fn paint_value(&mut self, value: &Value, ranges: &[Range<usize>]) {
+        if let PrinterNode::Value { val, .. } = self {
+            let mut painted = Vec::new();
+            highlight::write_value(&mut painted, value, ranges).unwrap();
+            *val = String::from_utf8(painted).unwrap();
         }
     }
 }
 
+// Dropping the nodes recursively would overflow the stack for deeply nested values
+impl Drop for PrinterNode {
+    fn drop(&mut self) {
+        let mut stack = Vec::new();
+        match self {
+            PrinterNode::Array { vec, .. } => stack.extend(vec.drain(..).map(|(_, v)| v)),
+            PrinterNode::Object { map, .. } => stack.extend(map.drain(..).map(|(_, v)| v)),
+            PrinterNode::Value { .. } => return,
+        }
+        while let Some(mut node) = stack.pop() {
+            match &mut node {
+                PrinterNode::Array { vec, .. } => stack.extend(vec.drain(..).map(|(_, v)| v)),
+                PrinterNode::Object { map, .. } => stack.extend(map.drain(..).map(|(_, v)| v)),
+                PrinterNode::Value { .. } => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,703 @@
+use std::{
+    borrow::Cow,
+    cell::RefCell,
+    fs::File,
+    io::{self, BufRead, BufReader, Cursor, Read},
+};
+
+use memmap2::Mmap;
+use serde_json::{value::RawValue, Value};
+
+use crate::errors::document_error::{DocumentError, Snippet};
+
+/// A document read from the input.
+pub struct Document<'a> {
+    /// The number of the document in the input, or of its line with JSON Lines, from 1.
+    pub index: usize,
+    pub content: Content<'a>,
+    /// The line of the input the document starts in, when it is JSON text, to place the errors
+    /// found when it is parsed.
+    pub line: Option<usize>,
+    /// The text before the document in its line, when it was extracted from a line of text.
+    pub prefix: Option<String>,
+}
+
+impl Document<'_> {
+    /// Copies the text borrowed from the input, so the document can be searched after the input
+    /// is closed.
+    pub fn into_owned(self) -> Document<'static> {
+        let content = match self.content {
+            Content::Text(text) => Content::Text(Cow::Owned(text.into_owned())),
+            Content::Value(json) => Content::Value(json),
+        };
+        Document {
+            index: self.index,
+            content,
+            line: self.line,
+            prefix: self.prefix,
+        }
+    }
+}
+
+/// What a document holds.
+pub enum Content<'a> {
+    /// JSON text, not parsed yet. Borrowed from the input when it is memory-mapped, so it isn't
+    /// copied.
+    Text(Cow<'a, str>),
+    /// A document already parsed while it was read.
+    Value(Value),
+}
+
+/// Where the documents are read from.
+pub enum Source<'a> {
+    Reader(Box<dyn Read + Send + 'a>),
+    /// The whole input, usually a memory-mapped file.
+    Bytes(&'a [u8]),
+}
+
+impl<'a> Source<'a> {
+    /// Returns the first `len` bytes of the input, or all of it if it is shorter, without consuming
+    /// them. A reader can't be rewound, so the bytes read from it are put back in front of it.
+    pub fn peek(self, len: usize) -> io::Result<(Vec<u8>, Source<'a>)> {
+        match self {
+            Source::Bytes(bytes) => Ok((bytes[..len.min(bytes.len())].to_vec(), self)),
+            Source::Reader(mut reader) => {
+                let mut start = Vec::with_capacity(len);
+                (&mut reader).take(len as u64).read_to_end(&mut start)?;
+                let reader = Cursor::new(start.clone()).chain(reader);
+                Ok((start, Source::Reader(Box::new(reader))))
+            }
+        }
+    }
+}
+
+/// Memory-maps a file, so its documents can be parsed in place instead of being copied out of it.
+/// Returns `None` for pipes and special files, which can't be mapped or report no size, and for
+/// empty files, which can't be mapped on every platform. Those are read through a buffer instead.
+pub fn map(file: &File) -> Option<Mmap> {
+    let metadata = file.metadata().ok()?;
+    if !metadata.is_file() || metadata.len() == 0 {
+        return None;
+    }
+    // Safety: the map is only read, but if another process truncates the file while it is being
+    // searched, reading past the new end fails with a bus error. Grep tools accept that risk for
+    // the speed, and the same is done here.
+    unsafe { Mmap::map(file) }.ok()
+}
+
+fn read_error(err: impl std::fmt::Display) -> DocumentError {
+    DocumentError::new(format!("Error reading the input: {}", err), 1)
+}
+
+/// Reads the JSON documents in the input one after another, and emits every document as soon as
+/// it is complete, before reading the next one. Documents can be any JSON value, separated by
+/// whitespace, or by nothing at all when they end with a bracket or a quote. Stops when `emit`
+/// returns false, or at the first error. With `recover`, an invalid document is reported and
+/// reading carries on at the next line starting with `{` or `[`, where the next document most
+/// likely starts, instead.
+///
+/// The documents are only checked to be valid, and emitted as text, to be parsed by whoever
+/// searches them.
+pub fn read_documents<'a>(
+    source: Source<'a>,
+    recover: bool,
+    emit: &mut dyn FnMut(Result<Document<'a>, DocumentError>) -> bool,
+) {
+    let mut index = 0;
+    // The line of the input where parsing (re)started
+    let mut first_line = 1;
+    match source {
+        Source::Reader(reader) => {
+            // Shared with the reader handed to the parser, to count the lines before every
+            // document, and to carry on after an error
+            let window = RefCell::new(Window::new(reader));
+            loop {
+                let base = window.borrow().position;
+                let reader = BufReader::new(WindowReader(&window));
+                let mut deserializer = serde_json::Deserializer::from_reader(reader);
+                deserializer.disable_recursion_limit();
+                let mut documents = deserializer.into_iter::<Box<RawValue>>();
+                let documents = std::iter::from_fn(|| {
+                    let raw = documents.next()?;
+                    Some(raw.map(|raw| {
+                        // The start of a long document may be gone, but not its end
+                        let end = window.borrow_mut().line_at(base + documents.byte_offset());
+                        let line = end - line_breaks(raw.get());
+                        (Cow::Owned(Box::<str>::from(raw).into_string()), line)
+                    }))
+                });
+                let Some(err) = emit_documents(documents, &mut index, emit) else {
+                    return;
+                };
+
+                let mut window = window.borrow_mut();
+                let line = first_line + err.line() - 1;
+                let (offset, snippet) = window.locate(line, err.column()).unzip();
+                let error = DocumentError::json(&err).at(line, err.column(), snippet);
+                if !emit(Err(error)) || !recover {
+                    return;
+                }
+                match offset.map(|offset| window.skip_to_document(offset)) {
+                    Some(Ok(lines)) => first_line = line + lines,
+                    Some(Err(err)) => {
+                        emit(Err(read_error(err)));
+                        return;
+                    }
+                    None => return,
+                }
+            }
+        }
+        Source::Bytes(bytes) => {
+            let mut start = 0;
+            let mut lines = Lines::default();
+            loop {
+                let text = &bytes[start..];
+                let mut deserializer = serde_json::Deserializer::from_slice(text);
+                deserializer.disable_recursion_limit();
+                let mut documents = deserializer.into_iter::<&RawValue>();
+                let documents = std::iter::from_fn(|| {
+                    let raw = documents.next()?;
+                    Some(raw.map(|raw| {
+                        let end = lines.advance(&bytes[lines.offset..start + documents.byte_offset()]);
+                        (Cow::Borrowed(raw.get()), end - line_breaks(raw.get()))
+                    }))
+                });
+                let Some(err) = emit_documents(documents, &mut index, emit) else {
+                    return;
+                };
+
+                let line = first_line + err.line() - 1;
+                let (offset, snippet) = locate(text, err.line(), err.column()).unzip();
+                let error = DocumentError::json(&err).at(line, err.column(), snippet);
+                if !emit(Err(error)) || !recover {
+                    return;
+                }
+                let Some(offset) = offset else { return };
+                let (skipped, lines) = next_document(&text[offset..]);
+                start += offset + skipped;
+                first_line = line + lines;
+            }
+        }
+    }
+}
+
+// Emits the documents, with the line they start in, until the first one that isn't valid, and
+// returns its error. Returns None
+// when all of them were emitted, or `emit` returned false.
+fn emit_documents<'a>(
+    documents: impl Iterator<Item = Result<(Cow<'a, str>, usize), serde_json::Error>>,
+    index: &mut usize,
+    emit: &mut dyn FnMut(Result<Document<'a>, DocumentError>) -> bool,
+) -> Option<serde_json::Error> {
+    for document in documents {
+        match document {
+            Ok((content, line)) => {
+                *index += 1;
+                let document = Document {
+                    index: *index,
+                    content: Content::Text(content),
+                    line: Some(line),
+                    prefix: None,
+                };
+                if !emit(Ok(document)) {
+                    return None;
+                }
+            }
+            Err(err) if err.is_io() => {
+                emit(Err(read_error(err)));
+                return None;
+            }
+            Err(err) => return Some(err),
+        }
+    }
+    None
+}
+
+// Finds the next line starting with `{` or `[`, after the first one. Returns its offset, or the
+// end of the text, and how many lines start before it.
+fn next_document(text: &[u8]) -> (usize, usize) {
+    let mut offset = 0;
+    let mut lines = 0;
+    while let Some(i) = text[offset..].iter().position(|&b| b == b'\n') {
+        offset += i + 1;
+        lines += 1;
+        if matches!(text.get(offset), Some(b'{' | b'[')) {
+            return (offset, lines);
+        }
+    }
+    (text.len(), lines)
+}
+
+// Finds the offset of an error in the text, from its line and column, and the text around it
+fn locate(text: &[u8], line: usize, column: usize) -> Option<(usize, Snippet)> {
+    let mut lines = text.split_inclusive(|&b| b == b'\n');
+    let line_start: usize = lines.by_ref().take(line.checked_sub(1)?).map(<[u8]>::len).sum();
+    let line_text = lines.next().unwrap_or_default();
+    let offset = (line_start + column.saturating_sub(1)).min(text.len());
+    Some((offset, Snippet::new(line_text, column)))
+}
+
+// How much of the input is kept behind what was handed to the parser. It has to hold what the
+// parser buffered, and the text shown before an error.
+const WINDOW_KEEP: usize = 64 * 1024;
+
+// Counts the lines of the input, up to an offset that only moves forward
+#[derive(Default)]
+struct Lines {
+    offset: usize,
+    count: usize,
+}
+
+impl Lines {
+    // Counts the lines in the bytes from the offset counted to, and returns the line they end in,
+    // from 1
+    fn advance(&mut self, bytes: &[u8]) -> usize {
+        self.offset += bytes.len();
+        self.count += line_breaks(bytes);
+        self.count + 1
+    }
+}
+
+fn line_breaks(text: impl AsRef<[u8]>) -> usize {
+    text.as_ref().iter().filter(|&&b| b == b'\n').count()
+}
+
+/// Keeps the last bytes read from the input, to show the text around an error, and to carry on
+/// reading after it, from bytes the parser had already buffered.
+struct Window<R> {
+    reader: R,
+    bytes: Vec<u8>,
+    // The offset of the first byte kept
+    start: usize,
+    // How many lines end before the first byte kept, and where the line it is in starts
+    lines: usize,
+    line_start: usize,
+    // The offset of the next byte to hand out
+    position: usize,
+    // The lines up to the end of the last document, which are counted before they are dropped
+    counted: Lines,
+}
+
+/// Hands out the bytes of a window, which is still there when the reader is dropped.
+struct WindowReader<'w, R>(&'w RefCell<Window<R>>);
+
+impl<R: Read> Window<R> {
+    fn new(reader: R) -> Self {
+        Window {
+            reader,
+            bytes: Vec::new(),
+            start: 0,
+            lines: 0,
+            line_start: 0,
+            position: 0,
+            counted: Lines::default(),
+        }
+    }
+
+    fn end(&self) -> usize {
+        self.start + self.bytes.len()
+    }
+
+    // Reads more of the input, dropping the bytes far enough behind. Returns false at its end.
+    fn fill(&mut self) -> io::Result<bool> {
+        let behind = self.position - self.start;
+        if behind > 2 * WINDOW_KEEP {
+            let kept = self.start + behind - WINDOW_KEEP;
+            if self.counted.offset < kept {
+                self.line_at(kept);
+            }
+            let dropped = &self.bytes[..behind - WINDOW_KEEP];
+            self.lines += dropped.iter().filter(|&&b| b == b'\n').count();
+            if let Some(last) = dropped.iter().rposition(|&b| b == b'\n') {
+                self.line_start = self.start + last + 1;
+            }
+            self.start += dropped.len();
+            self.bytes.drain(..behind - WINDOW_KEEP);
+        }
+
+        let len = self.bytes.len();
+        self.bytes.resize(len + WINDOW_KEEP, 0);
+        let read = loop {
+            match self.reader.read(&mut self.bytes[len..]) {
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                read => break read,
+            }
+        };
+        self.bytes.truncate(len + *read.as_ref().unwrap_or(&0));
+        Ok(read? > 0)
+    }
+
+    // Returns the line an offset is in. The offsets asked for can only move forward.
+    fn line_at(&mut self, offset: usize) -> usize {
+        let from = self.counted.offset - self.start;
+        self.counted.advance(&self.bytes[from..offset - self.start])
+    }
+
+    /// Finds the offset of an error, from its line and column, and the text around it, if they
+    /// are still kept.
+    fn locate(&self, line: usize, column: usize) -> Option<(usize, Snippet)> {
+        let line_start = match (line - 1).checked_sub(self.lines)? {
+            0 => self.line_start,
+            n => self.start + locate(&self.bytes, n + 1, 1)?.0,
+        };
+        // The start of a long line may be gone
+        let from = line_start.max(self.start);
+        let column = column.saturating_sub(from - line_start);
+        let (offset, snippet) = locate(&self.bytes[from - self.start..], 1, column)?;
+        Some((from + offset, snippet))
+    }
+
+    /// Carries on at the next line starting with `{` or `[`, after the line the offset is in.
+    /// Returns how many lines start before it.
+    fn skip_to_document(&mut self, offset: usize) -> io::Result<usize> {
+        self.position = offset;
+        let mut lines = 0;
+        loop {
+            let rest = &self.bytes[self.position - self.start..];
+            let Some(i) = rest.iter().position(|&b| b == b'\n') else {
+                self.position = self.end();
+                if !self.fill()? {
+                    return Ok(lines);
+                }
+                continue;
+            };
+            self.position += i + 1;
+            lines += 1;
+            if self.position == self.end() && !self.fill()? {
+                return Ok(lines);
+            }
+            if matches!(self.bytes[self.position - self.start], b'{' | b'[') {
+                return Ok(lines);
+            }
+        }
+    }
+}
+
+impl<R: Read> Read for WindowReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let window = &mut *self.0.borrow_mut();
+        if window.position == window.end() && !window.fill()? {
+            return Ok(0);
+        }
+        let available = &window.bytes[window.position - window.start..];
+        let len = available.len().min(buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        window.position += len;
+        Ok(len)
+    }
+}
+
+/// Reads JSON Lines, where every line is a document, and emits them as they are read. The lines
+/// are not checked to be valid, but empty lines are skipped. Stops at the first error reading the
+/// input, or when `emit` returns false.
+pub fn read_lines<'a>(
+    source: Source<'a>,
+    emit: &mut dyn FnMut(Result<Document<'a>, DocumentError>) -> bool,
+) {
+    let mut reader = match source {
+        Source::Reader(reader) => BufReader::new(reader),
+        Source::Bytes(bytes) => {
+            for (i, line) in bytes.split_inclusive(|&b| b == b'\n').enumerate() {
+                let document = match line_document(i + 1, line) {
+                    Ok(None) => continue,
+                    Ok(Some(content)) => Ok(Document {
+                        index: i + 1,
+                        content: Content::Text(Cow::Borrowed(content)),
+                        line: Some(i + 1),
+                        prefix: None,
+                    }),
+                    Err(err) => Err(err),
+                };
+                if !emit(document) {
+                    return;
+                }
+            }
+            return;
+        }
+    };
+
+    let mut line = Vec::new();
+    for index in 1.. {
+        line.clear();
+        match reader.read_until(b'\n', &mut line) {
+            Ok(0) => return,
+            Ok(_) => {}
+            Err(err) => {
+                emit(Err(read_error(err)));
+                return;
+            }
+        }
+
+        let document = match line_document(index, &line) {
+            Ok(None) => continue,
+            Ok(Some(content)) => Ok(Document {
+                index,
+                content: Content::Text(Cow::Owned(content.to_string())),
+                line: Some(index),
+                prefix: None,
+            }),
+            Err(err) => Err(err),
+        };
+        if !emit(document) {
+            return;
+        }
+    }
+}
+
+/// Reads the input line by line, like a log, and emits every JSON object or array found inside a
+/// line, with the text of the line before it. The rest of the text is skipped, and so are the lines without
+/// JSON. Stops at the first error reading the input, or when `emit` returns false.
+pub fn extract_documents<'a>(
+    source: Source<'a>,
+    emit: &mut dyn FnMut(Result<Document<'a>, DocumentError>) -> bool,
+) {
+    let mut reader: Box<dyn BufRead + 'a> = match source {
+        Source::Reader(reader) => Box::new(BufReader::new(reader)),
+        Source::Bytes(bytes) => Box::new(bytes),
+    };
+
+    let mut line = Vec::new();
+    for index in 1.. {
+        line.clear();
+        match reader.read_until(b'\n', &mut line) {
+            Ok(0) => return,
+            Ok(_) => {}
+            Err(err) => {
+                emit(Err(read_error(err)));
+                return;
+            }
+        }
+
+        let text = String::from_utf8_lossy(&line);
+        for (prefix, json) in embedded_json(&text) {
+            let document = Document {
+                index,
+                content: Content::Text(Cow::Owned(json.to_string())),
+                line: Some(index),
+                prefix: Some(prefix.to_string()),
+            };
+            if !emit(Ok(document)) {
+                return;
+            }
+        }
+    }
+}
+
+// The JSON objects and arrays in a line, with the text of the line before each of them. Every
+// bracket is a possible start, until one parses. When one doesn't, the search carries on from
+// where it stopped being valid, instead of from the next bracket, so a line is parsed about once
+// however many brackets it has. Valid JSON inside an invalid one is skipped with it.
+fn embedded_json(line: &str) -> Vec<(&str, &str)> {
+    let mut found = Vec::new();
+    let mut from = 0;
+    while let Some(offset) = line[from..].find(['{', '[']) {
+        let start = from + offset;
+        let mut values = serde_json::Deserializer::from_str(&line[start..]).into_iter::<&RawValue>();
+        match values.next() {
+            Some(Ok(json)) => {
+                found.push((&line[..start], json.get()));
+                from = start + values.byte_offset();
+            }
+            Some(Err(err)) => {
+                let text = &line.as_bytes()[start..];
+                let failed = locate(text, err.line(), err.column()).map_or(0, |(offset, _)| offset);
+                from = start + failed.max(1);
+                while !line.is_char_boundary(from) {
+                    from += 1;
+                }
+            }
+            None => from = start + 1,
+        }
+    }
+    found
+}
+
+/// Reads the whole input as text, for formats that can't be read as a stream.
+pub fn read_text(source: Source) -> Result<Cow<str>, DocumentError> {
+    let not_utf8 = || DocumentError::invalid("Not valid UTF-8".to_string());
+    match source {
+        Source::Bytes(bytes) => std::str::from_utf8(bytes)
+            .map(Cow::Borrowed)
+            .map_err(|_| not_utf8()),
+        Source::Reader(mut reader) => {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).map_err(read_error)?;
+            String::from_utf8(bytes).map(Cow::Owned).map_err(|_| not_utf8())
+        }
+    }
+}
+
+// The content of a line, or None if it is blank. The indentation is kept, so the columns of errors
+// are the ones in the input.
+fn line_document(index: usize, line: &[u8]) -> Result<Option<&str>, DocumentError> {
+    match std::str::from_utf8(line) {
+        Ok(content) if content.trim().is_empty() => Ok(None),
+        Ok(content) => Ok(Some(content.trim_end())),
+        Err(_) => Err(DocumentError::invalid("Not valid UTF-8".to_string()).in_line(index)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{embedded_json, read_documents, read_lines, Content, Document, Source};
+    use crate::errors::document_error::DocumentError;
+
+    enum Reading {
+        Documents,
+        Recovering,
+        Lines,
+    }
+
+    // Reads the input both from a reader and from bytes, which have to give the same documents
+    fn documents(input: &str, reading: Reading) -> Vec<Result<String, String>> {
+        let mut results = Vec::new();
+        for source in [
+            Source::Reader(Box::new(input.as_bytes())),
+            Source::Bytes(input.as_bytes()),
+        ] {
+            let mut documents = Vec::new();
+            let mut emit = |document: Result<Document, DocumentError>| {
+                let document = document.map(|d| match d.content {
+                    Content::Text(text) => format!("{}:{}", d.index, text),
+                    Content::Value(json) => format!("{}:{}", d.index, json),
+                });
+                documents.push(document.map_err(|e| e.to_string()));
+                true
+            };
+            match reading {
+                Reading::Documents => read_documents(source, false, &mut emit),
+                Reading::Recovering => read_documents(source, true, &mut emit),
+                Reading::Lines => read_lines(source, &mut emit),
+            }
+            results.push(documents);
+        }
+        assert_eq!(results[0], results[1]);
+        results.pop().unwrap()
+    }
+
+    #[test]
+    fn test_read_documents() {
+        let input = "{\"a\": \"}{\"}[\"ñ]\"]\n\"é\" 1 null\n{\"b\":\n  2}";
+        assert_eq!(
+            documents(input, Reading::Documents),
+            vec![
+                Ok("1:{\"a\": \"}{\"}".to_string()),
+                Ok("2:[\"ñ]\"]".to_string()),
+                Ok("3:\"é\"".to_string()),
+                Ok("4:1".to_string()),
+                Ok("5:null".to_string()),
+                Ok("6:{\"b\":\n  2}".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert_eq!(
+            documents("{} {\"a\": } {}", Reading::Documents),
+            vec![
+                Ok("1:{}".to_string()),
+                Err("line 1, column 10: Invalid JSON: expected value\n  {} {\"a\": } {}\n           ^"
+                    .to_string()),
+            ]
+        );
+        assert_eq!(documents("  \n", Reading::Documents), vec![]);
+    }
+
+    #[test]
+    fn test_recover() {
+        let input = "{\"a\": 1}\n{\"a\": x}\n\n{\"a\":\n 2 3}\n{\"a\": 4}\n[";
+        assert_eq!(
+            documents(input, Reading::Recovering),
+            vec![
+                Ok("1:{\"a\": 1}".to_string()),
+                Err("line 2, column 7: Invalid JSON: expected value\n  {\"a\": x}\n        ^"
+                    .to_string()),
+                Err("line 5, column 4: Invalid JSON: expected `,` or `}`\n   2 3}\n     ^"
+                    .to_string()),
+                Ok("2:{\"a\": 4}".to_string()),
+                Err("line 7, column 1: Invalid JSON: EOF while parsing a list\n  [\n  ^".to_string()),
+            ]
+        );
+
+        // The rest of a broken document is skipped, up to the line the next one starts in
+        let input = "{\n  \"a\": 1\n  \"b\": [\n    2\n  ]\n}\n{\"a\": 3}";
+        assert_eq!(
+            documents(input, Reading::Recovering),
+            vec![
+                Err("line 3, column 3: Invalid JSON: expected `,` or `}`\n    \"b\": [\n    ^"
+                    .to_string()),
+                Ok("1:{\"a\": 3}".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_recover_far_in_input() {
+        // Errors are found past what is kept of the input read
+        let valid = "{\"a\": [1, 2, 3]}\n".repeat(20_000);
+        let input = format!("{}{{\"b\": x}}\n{}", valid, valid);
+        let results = documents(&input, Reading::Recovering);
+
+        assert_eq!(results.len(), 40_001);
+        assert_eq!(
+            results[20_000],
+            Err("line 20001, column 7: Invalid JSON: expected value\n  {\"b\": x}\n        ^"
+                .to_string())
+        );
+        assert_eq!(results[40_000], Ok("40000:{\"a\": [1, 2, 3]}".to_string()));
+    }
+
+    #[test]
+    fn test_document_lines() {
+        // Still counted past what is kept of the input read, after an error, and after a document
+        // longer than what is kept
+        let input = format!(
+            "{}\n\n  {{\"a\":\n 1}}\n[1,\n2] 3\n{{\"b\": x}}\n[4]\n[{}1] 5",
+            "1 ".repeat(100_000),
+            "1,\n".repeat(100_000)
+        );
+        let mut results = Vec::new();
+        for source in [
+            Source::Reader(Box::new(input.as_bytes())),
+            Source::Bytes(input.as_bytes()),
+        ] {
+            let mut lines = Vec::new();
+            read_documents(source, true, &mut |document| {
+                lines.extend(document.ok().map(|d| d.line.unwrap()));
+                true
+            });
+            results.push(lines);
+        }
+        assert_eq!(results[0], results[1]);
+        assert_eq!(results[0].len(), 100_006);
+        assert!(results[0][..100_000].iter().all(|&line| line == 1));
+        assert_eq!(results[0][100_000..], [3, 5, 6, 8, 9, 100_009]);
+    }
+
+    #[test]
+    fn test_read_lines() {
+        assert_eq!(
+            documents("{\"a\": 1}\r\n\n  \n{\"a\": }\n2", Reading::Lines),
+            vec![
+                Ok("1:{\"a\": 1}".to_string()),
+                Ok("4:{\"a\": }".to_string()),
+                Ok("5:2".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_embedded_json() {
+        assert_eq!(
+            embedded_json("2026-10-01 12:00:01 [INFO] {\"event\": \"login\"} took [1, 2] ms\n"),
+            vec![
+                ("2026-10-01 12:00:01 [INFO] ", "{\"event\": \"login\"}"),
+                ("2026-10-01 12:00:01 [INFO] {\"event\": \"login\"} took ", "[1, 2]"),
+            ]
+        );
+        assert_eq!(embedded_json("{\"a\": {broken} [}\n"), vec![]);
+        assert_eq!(embedded_json("[x] {\"a\": [1]}"), vec![("[x] ", "{\"a\": [1]}")]);
+        assert_eq!(embedded_json("{\"a\": [1], ñ} [2]"), vec![("{\"a\": [1], ñ} ", "[2]")]);
+    }
+}
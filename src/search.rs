@@ -0,0 +1,227 @@
+use serde_json::Value;
+
+use crate::{
+    errors::document_error::{DocumentError, Snippet},
+    matcher::match_node::{Highlight, MatchNode},
+    matcher::{stream::StreamMatch, Automaton},
+    printers::{self, highlight},
+    utils::{json_utils, match_utils},
+    PrinterType,
+};
+
+/// Everything needed to search a document and print its matches. It is shared by all the worker
+/// threads.
+pub struct Search {
+    pub automaton: Automaton,
+    pub printer: PrinterType,
+    pub context: usize,
+    pub max_depth: Option<usize>,
+    /// The most matches to find in a single document.
+    pub max_count: Option<usize>,
+    /// Only count the matches, without printing them.
+    pub count_only: bool,
+    /// Print what doesn't match instead, see `print_inverted`.
+    pub invert: bool,
+    /// Paint the matching parts of the keys and values.
+    pub color: bool,
+    /// Search inside the strings that hold a JSON object or array, see `json_utils::parse_nested`.
+    pub parse_nested: bool,
+}
+
+/// The matches found in a document, already printed.
+pub struct Found {
+    pub output: Vec<u8>,
+    pub count: usize,
+    /// The line of the document in the input, printed before every line of output.
+    pub line: Option<usize>,
+    /// The text before the document in its line, printed after the line number.
+    pub prefix: Option<String>,
+    // Kept when there is a match limit, to print fewer matches if the limit is shared with other
+    // documents, and runs out in this one.
+    document: Option<(Value, Vec<Vec<MatchNode>>)>,
+}
+
+impl Drop for Found {
+    fn drop(&mut self) {
+        if let Some((json, _)) = self.document.take() {
+            json_utils::drop_deep(json);
+        }
+    }
+}
+
+impl Found {
+    /// The result of counting the matches of a document, without printing them.
+    pub fn counted(count: usize) -> Found {
+        Found {
+            output: Vec::new(),
+            count,
+            line: None,
+            prefix: None,
+            document: None,
+        }
+    }
+}
+
+impl Search {
+    /// Searches a complete JSON document, and returns the printed matches.
+    pub fn search(&self, content: &str) -> Result<Found, DocumentError> {
+        let json = json_utils::parse(content, self.max_depth).map_err(|err| {
+            let snippet = Snippet::find(content.as_bytes(), err.line(), err.column());
+            DocumentError::json(&err).at(err.line(), err.column(), snippet)
+        })?;
+        self.search_parsed(json)
+    }
+
+    /// Searches a document that is already parsed, from another format than JSON. As it wasn't
+    /// checked while it was parsed, it is checked not to be nested too deep first.
+    pub fn search_value(&self, json: Value) -> Result<Found, DocumentError> {
+        if let Some(max_depth) = self.max_depth {
+            if json_utils::depth(&json) > max_depth {
+                json_utils::drop_deep(json);
+                return Err(self.too_deep());
+            }
+        }
+        self.search_parsed(json)
+    }
+
+    fn search_parsed(&self, mut json: Value) -> Result<Found, DocumentError> {
+        let nested = match self.parse_nested {
+            true => match json_utils::parse_nested(&mut json, self.max_depth) {
+                Ok(nested) => nested,
+                Err(_) => {
+                    json_utils::drop_deep(json);
+                    return Err(self.too_deep());
+                }
+            },
+            false => Vec::new(),
+        };
+
+        if self.count_only {
+            let count = self.automaton.count_matches(&json, self.max_count);
+            json_utils::drop_deep(json);
+            return Ok(Found {
+                output: Vec::new(),
+                count,
+                line: None,
+                prefix: None,
+                document: None,
+            });
+        }
+
+        if self.invert {
+            let mut matches = self.automaton.find_matches(&json, None);
+            match_utils::mark_nested(&mut matches, &nested);
+            return Ok(Found {
+                output: Vec::new(),
+                count: matches.len(),
+                line: None,
+                prefix: None,
+                document: Some((json, matches)),
+            });
+        }
+
+        let mut matches = self.automaton.find_matches(&json, self.max_count);
+        match_utils::mark_nested(&mut matches, &nested);
+        let output = self.print(&json, &matches);
+
+        let mut found = Found {
+            output,
+            count: matches.len(),
+            line: None,
+            prefix: None,
+            document: None,
+        };
+        if self.max_count.is_some() {
+            found.document = Some((json, matches));
+        } else {
+            json_utils::drop_deep(json);
+        }
+        Ok(found)
+    }
+
+    /// Prints a match found while streaming a document, as a result of its own.
+    pub fn print_streamed(&self, m: &StreamMatch) -> Found {
+        let mut output = Vec::new();
+        if let Some(value) = &m.value {
+            let highlight = self.color.then(|| self.automaton.highlight_value(value, &m.path));
+            match self.printer {
+                PrinterType::Only => {
+                    let ranges = highlight.as_ref().map(|h| h.value.as_slice()).unwrap_or(&[]);
+                    highlight::write_value(&mut output, value, ranges).unwrap();
+                    output.push(b'\n');
+                }
+                _ => printers::path_printer::print_match(
+                    &m.path,
+                    value,
+                    highlight.as_ref(),
+                    true,
+                    &mut output,
+                ),
+            }
+        }
+        Found {
+            output,
+            count: 1,
+            line: None,
+            prefix: None,
+            document: None,
+        }
+    }
+
+    /// Keeps only the first `count` matches of a search.
+    pub fn truncate(&self, mut found: Found, count: usize) -> Found {
+        if count == 0 {
+            found.output.clear();
+            found.count = 0;
+        } else if count < found.count {
+            if let Some((json, matches)) = &found.document {
+                found.output = self.print(json, &matches[..count]);
+            }
+            found.count = count;
+        }
+        found
+    }
+
+    /// Prints an inverted search. In a stream of documents, the document is printed if it has no
+    /// matches. Otherwise, it is printed with its matches removed.
+    pub fn print_inverted(&self, found: &Found, stream: bool) -> Vec<u8> {
+        let mut output = Vec::new();
+        if let Some((json, matches)) = &found.document {
+            if stream {
+                printers::invert_printer::print_unmatched(json, matches, &mut output);
+            } else {
+                printers::invert_printer::print(json, matches, &mut output);
+            }
+        }
+        output
+    }
+
+    fn too_deep(&self) -> DocumentError {
+        DocumentError::invalid(format!(
+            "JSON nested deeper than the maximum depth of {}",
+            self.max_depth.unwrap_or_default()
+        ))
+    }
+
+    fn print(&self, json: &Value, matches: &[Vec<MatchNode>]) -> Vec<u8> {
+        let highlights: Option<Vec<Highlight>> = self.color.then(|| {
+            let highlight = |m: &Vec<MatchNode>| self.automaton.highlight(json, m);
+            matches.iter().map(highlight).collect()
+        });
+        let highlights = highlights.as_deref();
+
+        let mut output = Vec::new();
+        match self.printer {
+            PrinterType::Path => {
+                printers::path_printer::print(json, matches, highlights, self.context, &mut output)
+            }
+            PrinterType::Json => {
+                printers::json_printer::print(json, matches, highlights, self.context, &mut output)
+            }
+            PrinterType::Only => {
+                printers::only_printer::print(json, matches, highlights, self.context, &mut output)
+            }
+        }
+        output
+    }
+}
@@ -0,0 +1,65 @@
+use std::io::Read;
+
+use crate::{
+    errors::document_error::DocumentError,
+    search::{Found, Search},
+    utils::json_events::EventParser,
+};
+
+/// Searches the documents of the input while they are parsed, instead of reading them whole, so
+/// memory doesn't grow with the size of a document. Only the path to the current value is kept,
+/// and the matched values are built only to print them.
+///
+/// Matches are emitted in the order they start in the document, as soon as they are complete, and
+/// the input isn't read further once `emit` returns false.
+pub fn process<R: Read>(
+    reader: R,
+    search: &Search,
+    emit: &mut dyn FnMut(Result<Found, DocumentError>) -> bool,
+) {
+    let mut parser = EventParser::new(reader).with_max_depth(search.max_depth);
+    loop {
+        let first = match parser.next_event() {
+            Ok(Some(event)) => event,
+            Ok(None) => return,
+            Err(err) => {
+                emit(Err(err));
+                return;
+            }
+        };
+
+        let mut count = 0;
+        let mut keep_going = true;
+        let complete =
+            search
+                .automaton
+                .stream_document(&mut parser, first, !search.count_only, &mut |m| {
+                    count += 1;
+                    if !search.count_only {
+                        keep_going = emit(Ok(search.print_streamed(&m)));
+                    }
+                    keep_going && search.max_count.is_none_or(|max| count < max)
+                });
+
+        let complete = match complete {
+            Ok(complete) => complete,
+            Err(err) => {
+                emit(Err(err));
+                return;
+            }
+        };
+        if search.count_only {
+            keep_going = emit(Ok(Found::counted(count)));
+        }
+        if !keep_going {
+            return;
+        }
+        // The document reached its match limit, but the next one still has to be found
+        if !complete {
+            if let Err(err) = parser.skip_document() {
+                emit(Err(err));
+                return;
+            }
+        }
+    }
+}
@@ -0,0 +1,312 @@
+use std::ops::Range;
+
+/// A precompiled wildcard pattern, where `*` matches any number of characters, and `?` matches
+/// exactly one. The `?`s at the end of the pattern can match the end of the haystack too, so
+/// `ab?` matches `ab`.
+///
+/// The pattern is split by its stars into literal segments. The first and last segments are
+/// anchored to the start and the end of the haystack, unless the pattern starts or ends with a
+/// star, and the segments in between are searched for left to right, taking the leftmost
+/// occurrence each time. Searching uses a bit-parallel (shift-and) automaton, so matching takes
+/// time proportional to the haystack length, without recursion or backtracking.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Glob {
+    segments: Vec<Segment>,
+    has_star: bool,
+    anchored_start: bool,
+    anchored_end: bool,
+    // The same pattern without its last trailing `?`, tried when this one doesn't match
+    shorter: Option<Box<Glob>>,
+}
+
+/// A part of the pattern without stars. `None` stands for a `?`.
+#[derive(Debug, Clone, PartialEq)]
+struct Segment {
+    chars: Vec<Option<char>>,
+    // Bit i of the mask of a character is set if it can be the i-th character of the segment
+    masks: Vec<(char, Vec<u64>)>,
+    any_mask: Vec<u64>,
+}
+
+impl Segment {
+    fn new(chars: Vec<Option<char>>) -> Segment {
+        let words = chars.len().div_ceil(64);
+        let mut any_mask = vec![0u64; words];
+        for (i, c) in chars.iter().enumerate() {
+            if c.is_none() {
+                any_mask[i / 64] |= 1 << (i % 64);
+            }
+        }
+
+        let mut masks: Vec<(char, Vec<u64>)> = Vec::new();
+        for (i, c) in chars.iter().enumerate() {
+            if let Some(c) = c {
+                let mask = match masks.binary_search_by_key(c, |(k, _)| *k) {
+                    Ok(pos) => &mut masks[pos].1,
+                    Err(pos) => {
+                        masks.insert(pos, (*c, any_mask.clone()));
+                        &mut masks[pos].1
+                    }
+                };
+                mask[i / 64] |= 1 << (i % 64);
+            }
+        }
+
+        Segment {
+            chars,
+            masks,
+            any_mask,
+        }
+    }
+
+    fn mask(&self, c: char) -> &[u64] {
+        match self.masks.binary_search_by_key(&c, |(k, _)| *k) {
+            Ok(pos) => &self.masks[pos].1,
+            Err(_) => &self.any_mask,
+        }
+    }
+
+    fn matches_char(expected: &Option<char>, c: char) -> bool {
+        expected.map(|e| e == c).unwrap_or(true)
+    }
+
+    /// If the haystack starts with the segment, returns the byte length of the matched prefix.
+    fn match_prefix(&self, haystack: &str) -> Option<usize> {
+        let mut chars = haystack.char_indices();
+        for expected in &self.chars {
+            match chars.next() {
+                Some((_, c)) if Self::matches_char(expected, c) => {}
+                _ => return None,
+            }
+        }
+        Some(chars.next().map(|(i, _)| i).unwrap_or(haystack.len()))
+    }
+
+    /// If the haystack ends with the segment, returns the byte offset where the matched suffix
+    /// starts.
+    fn match_suffix(&self, haystack: &str) -> Option<usize> {
+        let mut chars = haystack.char_indices().rev();
+        let mut start = haystack.len();
+        for expected in self.chars.iter().rev() {
+            match chars.next() {
+                Some((i, c)) if Self::matches_char(expected, c) => start = i,
+                _ => return None,
+            }
+        }
+        Some(start)
+    }
+
+    /// Finds the leftmost occurrence of the segment, returning its byte range.
+    fn find(&self, haystack: &str) -> Option<(usize, usize)> {
+        let len = self.chars.len();
+        if len == 0 {
+            return Some((0, 0));
+        }
+
+        let last_word = (len - 1) / 64;
+        let last_bit = 1 << ((len - 1) % 64);
+        let mut state = vec![0u64; last_word + 1];
+        // Start byte offsets of the last `len` characters, to recover where the occurrence begins
+        let mut starts = vec![0; len];
+
+        for (n, (i, c)) in haystack.char_indices().enumerate() {
+            starts[n % len] = i;
+
+            let mask = self.mask(c);
+            let mut carry = 1;
+            for (word, mask) in state.iter_mut().zip(mask) {
+                let next_carry = *word >> 63;
+                *word = ((*word << 1) | carry) & mask;
+                carry = next_carry;
+            }
+
+            if state[last_word] & last_bit != 0 {
+                let start = starts[(n + 1) % len];
+                return Some((start, i + c.len_utf8()));
+            }
+        }
+        None
+    }
+}
+
+impl Glob {
+    pub fn new(pattern: &str) -> Glob {
+        let segments = pattern
+            .split('*')
+            .filter(|s| !s.is_empty())
+            .map(|s| Segment::new(s.chars().map(|c| (c != '?').then_some(c)).collect()))
+            .collect();
+
+        let trailing = pattern.trim_end_matches(['?', '*']).len();
+        let shorter = pattern[trailing..].rfind('?').map(|i| {
+            let i = trailing + i;
+            Box::new(Glob::new(&format!("{}{}", &pattern[..i], &pattern[i + 1..])))
+        });
+
+        Glob {
+            segments,
+            has_star: pattern.contains('*'),
+            anchored_start: !pattern.starts_with('*'),
+            anchored_end: !pattern.ends_with('*'),
+            shorter,
+        }
+    }
+
+    pub fn is_match(&self, haystack: &str) -> bool {
+        self.match_segments(haystack, |_| {})
+            || self.shorter.as_ref().is_some_and(|g| g.is_match(haystack))
+    }
+
+    /// Returns the byte ranges of the haystack matched by the characters of the pattern that
+    /// aren't stars, in order, or `None` if it doesn't match. A pattern made only of stars matches
+    /// the whole haystack.
+    pub fn find_match(&self, haystack: &str) -> Option<Vec<Range<usize>>> {
+        if self.segments.is_empty() {
+            let whole = (!haystack.is_empty()).then_some(0..haystack.len());
+            return self.is_match(haystack).then(|| whole.into_iter().collect());
+        }
+
+        let mut ranges = Vec::new();
+        if !self.match_segments(haystack, |range| ranges.push(range)) {
+            return self.shorter.as_ref()?.find_match(haystack);
+        }
+        ranges.sort_by_key(|r| r.start);
+        ranges.retain(|r| !r.is_empty());
+        Some(ranges)
+    }
+
+    // Calls `on_segment` with the range each segment matched, in no particular order
+    fn match_segments(&self, haystack: &str, mut on_segment: impl FnMut(Range<usize>)) -> bool {
+        if !self.has_star {
+            return match self.segments.first() {
+                Some(segment) if segment.match_prefix(haystack) == Some(haystack.len()) => {
+                    on_segment(0..haystack.len());
+                    true
+                }
+                Some(_) => false,
+                None => haystack.is_empty(),
+            };
+        }
+
+        let mut segments = self.segments.as_slice();
+        let mut start = 0;
+        let mut end = haystack.len();
+
+        if self.anchored_start {
+            let Some((first, rest)) = segments.split_first() else {
+                return true;
+            };
+            match first.match_prefix(haystack) {
+                Some(len) => start = len,
+                None => return false,
+            }
+            on_segment(0..start);
+            segments = rest;
+        }
+
+        if self.anchored_end {
+            let Some((last, rest)) = segments.split_last() else {
+                return true;
+            };
+            match last.match_suffix(&haystack[start..]) {
+                Some(offset) => end = start + offset,
+                None => return false,
+            }
+            on_segment(end..haystack.len());
+            segments = rest;
+        }
+
+        for segment in segments {
+            match segment.find(&haystack[start..end]) {
+                Some((segment_start, segment_end)) => {
+                    on_segment(start + segment_start..start + segment_end);
+                    start += segment_end;
+                }
+                None => return false,
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Glob;
+
+    #[test]
+    fn test_glob() {
+        assert!(Glob::new("a*b*c").is_match("aXbYc"));
+        assert!(Glob::new("ab*ba").is_match("aba*ba"));
+        assert!(!Glob::new("ab*ba").is_match("aba"));
+        assert!(Glob::new("*?").is_match("é"));
+        assert!(Glob::new("?").is_match(""));
+        assert!(Glob::new("a*??").is_match("a"));
+        assert!(!Glob::new("?a").is_match(""));
+        assert!(Glob::new("*ñ?ñ*").is_match("aññxñ"));
+        assert!(Glob::new("*c?d*").is_match("cccdx"));
+    }
+
+    #[test]
+    fn test_long_segment() {
+        let segment = "ab?".repeat(40);
+        let haystack = format!("{}{}x", "ab".repeat(100), "abc".repeat(40));
+        assert!(Glob::new(&format!("*{}*", segment)).is_match(&haystack));
+        assert!(!Glob::new(&format!("*{}*", segment)).is_match(&"ab".repeat(200)));
+    }
+
+    #[test]
+    fn test_pathological() {
+        let haystack = "a".repeat(100_000);
+        assert!(!Glob::new("*a*a*a*a*a*a*a*b").is_match(&haystack));
+        assert!(Glob::new("*a*a*a*a*a*a*a*").is_match(&haystack));
+    }
+
+    #[test]
+    #[allow(clippy::single_range_in_vec_init)]
+    fn test_find_match() {
+        assert_eq!(Glob::new("a*b*c").find_match("aXbYc"), Some(vec![0..1, 2..3, 4..5]));
+        assert_eq!(Glob::new("*na?e*").find_match("username"), Some(vec![4..8]));
+        assert_eq!(Glob::new("*ñ").find_match("aññ"), Some(vec![3..5]));
+        assert_eq!(Glob::new("id").find_match("id"), Some(vec![0..2]));
+        assert_eq!(Glob::new("*").find_match("id"), Some(vec![0..2]));
+        assert_eq!(Glob::new("*").find_match(""), Some(vec![]));
+        assert_eq!(Glob::new("a*b").find_match("ac"), None);
+        assert_eq!(Glob::new("ab?").find_match("ab"), Some(vec![0..2]));
+    }
+
+    #[test]
+    fn test_wildcards() {
+        let matches = |haystack: &str, pattern: &str| Glob::new(pattern).is_match(haystack);
+
+        // No wildcard
+        assert!(matches("", "")); // Empty
+        assert!(matches("abc", "abc")); // Exact
+
+        // *
+        // Matches
+        assert!(matches("abc", "a*c")); // Wildcard matches one
+        assert!(matches("abc", "a*")); // Wildcard matches to the end
+        assert!(matches("abc", "*c")); // Wildcard matches from the start
+        assert!(matches("abc", "*")); // Wildcard matches everything
+        assert!(matches("abc", "*b*")); // Wildcard matches beginning and end
+        assert!(matches("abc", "a**c")); // Double wildcard center
+        assert!(matches("abc", "**b**")); // Double wildcard outside
+        assert!(matches("abc", "*abc*")); // Wildcard matches nothing
+        assert!(matches("abc", "a****c")); // lots of wildcards
+
+        // No matches
+        assert!(!matches("abc", "a")); // No wildcard
+        assert!(!matches("abc", "b*")); // Wildcard end
+        assert!(!matches("abc", "*b")); // Wildcard start
+        assert!(!matches("abc", "*d*")); // Multiple wildcards
+
+        // ?
+        assert!(matches("abc", "a?c")); // Single wildcard
+        assert!(matches("abc", "??c")); // Double wildcard
+        assert!(matches("abc", "?bc")); // Start
+        assert!(matches("abc", "ab?")); // End
+        assert!(matches("ab", "ab?")); // End matches nothing
+        assert!(matches("a", "a?*?")); // End matches nothing, with stars
+        assert!(!matches("ab", "a?c?")); // Only the end matches nothing
+    }
+}
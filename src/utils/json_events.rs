@@ -0,0 +1,411 @@
+use std::io::{BufRead, BufReader, Read};
+
+use serde_json::{Number, Value};
+
+use crate::errors::document_error::DocumentError;
+
+/// A piece of a JSON document, as it is parsed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    StartObject,
+    EndObject,
+    StartArray,
+    EndArray,
+    /// The key of the next value in an object
+    Key(String),
+    /// A string, number, boolean or null
+    Scalar(Value),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Expect {
+    /// A new document, or the end of the input
+    Document,
+    /// A value inside a container
+    Value,
+    /// The first value of an array, or its end
+    FirstElement,
+    /// The first key of an object, or its end
+    FirstKey,
+    /// A comma, or the end of the container
+    Next,
+}
+
+/// Parses JSON documents one after another into events, without building them. Only the kind of
+/// the containers the parser is in is kept, so memory doesn't grow with the size of the
+/// documents, and there is no recursion.
+pub struct EventParser<R: Read> {
+    reader: BufReader<R>,
+    // The containers the parser is in, true for objects
+    stack: Vec<bool>,
+    expect: Expect,
+    max_depth: Option<usize>,
+    // Where the parser is in the input, to report errors. Lines are counted from 1.
+    offset: usize,
+    line: usize,
+    line_start: usize,
+}
+
+impl<R: Read> EventParser<R> {
+    pub fn new(reader: R) -> Self {
+        EventParser {
+            reader: BufReader::new(reader),
+            stack: Vec::new(),
+            expect: Expect::Document,
+            max_depth: None,
+            offset: 0,
+            line: 1,
+            line_start: 0,
+        }
+    }
+
+    /// Rejects documents nested more than `max_depth` levels deep.
+    pub fn with_max_depth(mut self, max_depth: Option<usize>) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// How many containers the parser is in. It is 0 between documents.
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Returns the next event, or `None` at the end of the input, between documents.
+    pub fn next_event(&mut self) -> Result<Option<Event>, DocumentError> {
+        self.skip_whitespace()?;
+        let Some(byte) = self.peek()? else {
+            return match self.expect {
+                Expect::Document => Ok(None),
+                _ => Err(self.invalid()),
+            };
+        };
+
+        let event = match (self.expect, byte) {
+            (Expect::FirstElement, b']') | (Expect::Next, b']')
+                if self.stack.last() == Some(&false) =>
+            {
+                self.bump();
+                self.end_container(Event::EndArray)
+            }
+            (Expect::FirstKey, b'}') | (Expect::Next, b'}') if self.stack.last() == Some(&true) => {
+                self.bump();
+                self.end_container(Event::EndObject)
+            }
+            (Expect::Next, b',') => {
+                self.bump();
+                if self.stack.last() == Some(&true) {
+                    self.key()?
+                } else {
+                    self.skip_whitespace()?;
+                    self.value()?
+                }
+            }
+            (Expect::FirstKey, _) => self.key()?,
+            (Expect::Document, _) | (Expect::Value, _) | (Expect::FirstElement, _) => {
+                self.value()?
+            }
+            _ => return Err(self.invalid()),
+        };
+        Ok(Some(event))
+    }
+
+    /// Skips the events until the end of the current document.
+    pub fn skip_document(&mut self) -> Result<(), DocumentError> {
+        while self.depth() > 0 {
+            if self.next_event()?.is_none() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn end_container(&mut self, event: Event) -> Event {
+        self.stack.pop();
+        self.end_value();
+        event
+    }
+
+    fn end_value(&mut self) {
+        self.expect = if self.stack.is_empty() {
+            Expect::Document
+        } else {
+            Expect::Next
+        };
+    }
+
+    fn key(&mut self) -> Result<Event, DocumentError> {
+        self.skip_whitespace()?;
+        if self.peek()? != Some(b'"') {
+            return Err(self.invalid());
+        }
+        self.bump();
+        let key = self.string()?;
+
+        self.skip_whitespace()?;
+        if self.peek()? != Some(b':') {
+            return Err(self.invalid());
+        }
+        self.bump();
+        self.expect = Expect::Value;
+        Ok(Event::Key(key))
+    }
+
+    fn value(&mut self) -> Result<Event, DocumentError> {
+        let Some(byte) = self.peek()? else {
+            return Err(self.invalid());
+        };
+        let event = match byte {
+            b'{' | b'[' => {
+                self.bump();
+                if self.max_depth.is_some_and(|max| self.stack.len() >= max) {
+                    return Err(DocumentError::invalid(format!(
+                        "JSON nested deeper than the maximum depth of {}",
+                        self.max_depth.unwrap()
+                    )));
+                }
+                self.stack.push(byte == b'{');
+                if byte == b'{' {
+                    self.expect = Expect::FirstKey;
+                    return Ok(Event::StartObject);
+                }
+                self.expect = Expect::FirstElement;
+                return Ok(Event::StartArray);
+            }
+            b'"' => {
+                self.bump();
+                Value::String(self.string()?)
+            }
+            b't' => self.literal(b"true", Value::Bool(true))?,
+            b'f' => self.literal(b"false", Value::Bool(false))?,
+            b'n' => self.literal(b"null", Value::Null)?,
+            b'-' | b'0'..=b'9' => self.number()?,
+            _ => return Err(self.invalid()),
+        };
+        self.end_value();
+        Ok(Event::Scalar(event))
+    }
+
+    fn literal(&mut self, literal: &[u8], value: Value) -> Result<Value, DocumentError> {
+        for &expected in literal {
+            if self.peek()? != Some(expected) {
+                return Err(self.invalid());
+            }
+            self.bump();
+        }
+        Ok(value)
+    }
+
+    fn number(&mut self) -> Result<Value, DocumentError> {
+        let mut text = String::new();
+        while let Some(byte) = self.peek()? {
+            if !matches!(byte, b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9') {
+                break;
+            }
+            text.push(byte as char);
+            self.bump();
+        }
+        // Checks the grammar, and keeps the exact digits
+        let number: Number = serde_json::from_str(&text).map_err(|_| self.invalid())?;
+        Ok(Value::Number(number))
+    }
+
+    // Reads a string after its opening quote
+    fn string(&mut self) -> Result<String, DocumentError> {
+        let mut bytes = Vec::new();
+        loop {
+            let buffer = self.reader.fill_buf().map_err(read_error)?;
+            if buffer.is_empty() {
+                return Err(self.invalid());
+            }
+            let end = buffer
+                .iter()
+                .position(|&b| b == b'"' || b == b'\\' || b < 0x20)
+                .unwrap_or(buffer.len());
+            bytes.extend_from_slice(&buffer[..end]);
+            let stop = buffer.get(end).copied();
+            self.reader.consume(end);
+            self.offset += end;
+
+            match stop {
+                None => continue,
+                Some(b'"') => {
+                    self.bump();
+                    return String::from_utf8(bytes).map_err(|_| self.invalid());
+                }
+                Some(b'\\') => {
+                    self.bump();
+                    self.escape(&mut bytes)?;
+                }
+                Some(_) => return Err(self.invalid()),
+            }
+        }
+    }
+
+    fn escape(&mut self, bytes: &mut Vec<u8>) -> Result<(), DocumentError> {
+        let Some(byte) = self.peek()? else {
+            return Err(self.invalid());
+        };
+        self.bump();
+        let c = match byte {
+            b'"' => '"',
+            b'\\' => '\\',
+            b'/' => '/',
+            b'b' => '\u{8}',
+            b'f' => '\u{c}',
+            b'n' => '\n',
+            b'r' => '\r',
+            b't' => '\t',
+            b'u' => {
+                let high = self.hex4()?;
+                let code = if (0xD800..0xDC00).contains(&high) {
+                    if self.peek()? != Some(b'\\') {
+                        return Err(self.invalid());
+                    }
+                    self.bump();
+                    if self.peek()? != Some(b'u') {
+                        return Err(self.invalid());
+                    }
+                    self.bump();
+                    let low = self.hex4()?;
+                    if !(0xDC00..0xE000).contains(&low) {
+                        return Err(self.invalid());
+                    }
+                    0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+                } else {
+                    high
+                };
+                char::from_u32(code).ok_or_else(|| self.invalid())?
+            }
+            _ => return Err(self.invalid()),
+        };
+        let mut utf8 = [0; 4];
+        bytes.extend_from_slice(c.encode_utf8(&mut utf8).as_bytes());
+        Ok(())
+    }
+
+    fn hex4(&mut self) -> Result<u32, DocumentError> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let digit = self
+                .peek()?
+                .and_then(|b| (b as char).to_digit(16))
+                .ok_or_else(|| self.invalid())?;
+            self.bump();
+            code = code * 16 + digit;
+        }
+        Ok(code)
+    }
+
+    fn skip_whitespace(&mut self) -> Result<(), DocumentError> {
+        loop {
+            let buffer = self.reader.fill_buf().map_err(read_error)?;
+            let skipped = buffer
+                .iter()
+                .position(|b| !b.is_ascii_whitespace())
+                .unwrap_or(buffer.len());
+            let at_end = skipped < buffer.len() || buffer.is_empty();
+            if let Some(last) = buffer[..skipped].iter().rposition(|&b| b == b'\n') {
+                self.line += buffer[..skipped].iter().filter(|&&b| b == b'\n').count();
+                self.line_start = self.offset + last + 1;
+            }
+            self.offset += skipped;
+            self.reader.consume(skipped);
+            if at_end {
+                return Ok(());
+            }
+        }
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>, DocumentError> {
+        let buffer = self.reader.fill_buf().map_err(read_error)?;
+        Ok(buffer.first().copied())
+    }
+
+    fn bump(&mut self) {
+        self.reader.consume(1);
+        self.offset += 1;
+    }
+
+    // Newlines are only skipped as whitespace, so the position is always known
+    fn invalid(&self) -> DocumentError {
+        let column = self.offset - self.line_start + 1;
+        DocumentError::invalid("Invalid JSON".to_string()).at(self.line, column, None)
+    }
+}
+
+fn read_error(err: std::io::Error) -> DocumentError {
+    DocumentError::new(format!("Error reading the input: {}", err), 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{Event, EventParser};
+
+    fn events(input: &str) -> Result<Vec<Event>, String> {
+        let mut parser = EventParser::new(input.as_bytes());
+        let mut events = Vec::new();
+        while let Some(event) = parser.next_event().map_err(|e| e.to_string())? {
+            events.push(event);
+        }
+        Ok(events)
+    }
+
+    #[test]
+    fn test_events() {
+        assert_eq!(
+            events(r#"{"a": [1, "}\"ñé😀", {}], "b": null} 2.50 []"#),
+            Ok(vec![
+                Event::StartObject,
+                Event::Key("a".to_string()),
+                Event::StartArray,
+                Event::Scalar(json!(1)),
+                Event::Scalar(json!("}\"ñé😀")),
+                Event::StartObject,
+                Event::EndObject,
+                Event::EndArray,
+                Event::Key("b".to_string()),
+                Event::Scalar(json!(null)),
+                Event::EndObject,
+                Event::Scalar(serde_json::from_str("2.50").unwrap()),
+                Event::StartArray,
+                Event::EndArray,
+            ])
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        for input in [
+            r#"{"a" 1}"#,
+            "[1,]",
+            "[1 2]",
+            r#"{"a": 1,}"#,
+            "[}",
+            "[1",
+            "01",
+            "\"a\u{1}\"",
+        ] {
+            let err = events(input).unwrap_err();
+            assert!(err.ends_with(": Invalid JSON"), "{}: {}", input, err);
+        }
+    }
+
+    #[test]
+    fn test_invalid_position() {
+        assert_eq!(
+            events("{\"a\": \"ñ\",\n  \"b\": [1 2]}"),
+            Err("line 2, column 11: Invalid JSON".to_string())
+        );
+        assert_eq!(events("[\n\n"), Err("line 3, column 1: Invalid JSON".to_string()));
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let mut parser = EventParser::new("[[[]]]".as_bytes()).with_max_depth(Some(2));
+        assert!(parser.next_event().is_ok());
+        assert!(parser.next_event().is_ok());
+        assert!(parser.next_event().is_err());
+    }
+}
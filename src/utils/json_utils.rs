@@ -0,0 +1,377 @@
+use std::{
+    fmt,
+    io::{self, Write},
+};
+
+use serde::{
+    de::{self, DeserializeSeed, MapAccess, SeqAccess, Visitor},
+    forward_to_deserialize_any, Deserialize, Deserializer,
+};
+use serde_json::Value;
+
+use crate::matcher::match_node::MatchNode;
+
+/// Parses a JSON document, failing as soon as it is nested more than `max_depth` levels deep,
+/// instead of parsing the whole of it first. There is no other limit on how deeply nested it can
+/// be, the stack is grown on the heap as needed, instead of overflowing.
+pub fn parse(content: &str, max_depth: Option<usize>) -> serde_json::Result<Value> {
+    let mut deserializer = serde_json::Deserializer::from_str(content);
+    deserializer.disable_recursion_limit();
+    let deserializer = Limited {
+        inner: serde_stacker::Deserializer::new(&mut deserializer),
+        depth: 0,
+        max_depth: max_depth.unwrap_or(usize::MAX),
+    };
+    let value = Value::deserialize(deserializer)?;
+    Ok(value)
+}
+
+/// Wraps the deserializer of a value, and everything used to deserialize the values inside it,
+/// to count how many arrays and objects they are in. The value itself is deserialized by serde.
+///
+/// Arrays nested too deep are rejected when they are reached. Numbers with arbitrary precision
+/// are maps too, and their text is deserialized as a string, so objects are only rejected at
+/// their first value, or at their end when they are empty.
+struct Limited<T> {
+    inner: T,
+    // The arrays and objects the value is in
+    depth: usize,
+    max_depth: usize,
+}
+
+impl<T> Limited<T> {
+    fn wrap<U>(&self, inner: U, depth: usize) -> Limited<U> {
+        Limited {
+            inner,
+            depth,
+            max_depth: self.max_depth,
+        }
+    }
+
+    fn too_deep<E: de::Error>(&self) -> E {
+        E::custom(format!(
+            "JSON nested deeper than the maximum depth of {}",
+            self.max_depth
+        ))
+    }
+}
+
+impl<'de, D: Deserializer<'de>> Deserializer<'de> for Limited<D> {
+    type Error = D::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, D::Error> {
+        if self.depth > self.max_depth {
+            return Err(self.too_deep());
+        }
+        let visitor = self.wrap(visitor, self.depth);
+        self.inner.deserialize_any(visitor)
+    }
+
+    // The text of a number, with arbitrary precision
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, D::Error> {
+        self.inner.deserialize_str(visitor)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char string bytes byte_buf option
+        unit unit_struct newtype_struct seq tuple tuple_struct map struct enum identifier
+        ignored_any
+    }
+}
+
+impl<'de, V: Visitor<'de>> Visitor<'de> for Limited<V> {
+    type Value = V::Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.inner.expecting(formatter)
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<V::Value, E> {
+        self.inner.visit_bool(v)
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<V::Value, E> {
+        self.inner.visit_i64(v)
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<V::Value, E> {
+        self.inner.visit_u64(v)
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<V::Value, E> {
+        self.inner.visit_f64(v)
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<V::Value, E> {
+        self.inner.visit_str(v)
+    }
+
+    fn visit_borrowed_str<E: de::Error>(self, v: &'de str) -> Result<V::Value, E> {
+        self.inner.visit_borrowed_str(v)
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<V::Value, E> {
+        self.inner.visit_string(v)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<V::Value, E> {
+        self.inner.visit_unit()
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<V::Value, E> {
+        self.inner.visit_none()
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<V::Value, A::Error> {
+        if self.depth + 1 > self.max_depth {
+            return Err(self.too_deep());
+        }
+        let seq = self.wrap(seq, self.depth + 1);
+        self.inner.visit_seq(seq)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<V::Value, A::Error> {
+        let map = self.wrap(map, self.depth + 1);
+        self.inner.visit_map(map)
+    }
+}
+
+impl<'de, A: SeqAccess<'de>> SeqAccess<'de> for Limited<A> {
+    type Error = A::Error;
+
+    fn next_element_seed<S: DeserializeSeed<'de>>(
+        &mut self,
+        seed: S,
+    ) -> Result<Option<S::Value>, A::Error> {
+        let seed = self.wrap(seed, self.depth);
+        self.inner.next_element_seed(seed)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+impl<'de, A: MapAccess<'de>> MapAccess<'de> for Limited<A> {
+    type Error = A::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, A::Error> {
+        let key = self.inner.next_key_seed(seed)?;
+        if key.is_none() && self.depth > self.max_depth {
+            return Err(self.too_deep());
+        }
+        Ok(key)
+    }
+
+    fn next_value_seed<S: DeserializeSeed<'de>>(&mut self, seed: S) -> Result<S::Value, A::Error> {
+        let seed = self.wrap(seed, self.depth);
+        self.inner.next_value_seed(seed)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+impl<'de, S: DeserializeSeed<'de>> DeserializeSeed<'de> for Limited<S> {
+    type Value = S::Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<S::Value, D::Error> {
+        let deserializer = self.wrap(deserializer, self.depth);
+        self.inner.deserialize(deserializer)
+    }
+}
+
+/// Drops the value without recursion, which would overflow the stack for deeply nested values.
+pub fn drop_deep(value: Value) {
+    let mut stack = vec![value];
+    while let Some(value) = stack.pop() {
+        match value {
+            Value::Array(vec) => stack.extend(vec),
+            Value::Object(map) => stack.extend(map.into_iter().map(|(_, v)| v)),
+            _ => {}
+        }
+    }
+}
+
+/// Returns how many levels of arrays and objects are nested in the value. Scalars have depth 0.
+pub fn depth(value: &Value) -> usize {
+    let mut max_depth = 0;
+    let mut stack = vec![(value, 0)];
+    while let Some((value, depth)) = stack.pop() {
+        match value {
+            Value::Array(vec) => stack.extend(vec.iter().map(|v| (v, depth + 1))),
+            Value::Object(map) => stack.extend(map.values().map(|v| (v, depth + 1))),
+            _ => continue,
+        }
+        max_depth = max_depth.max(depth + 1);
+    }
+    max_depth
+}
+
+/// Replaces the strings holding a JSON object or array with their parsed value, so they can be
+/// searched like the rest of the document. The parsed values are searched for strings holding
+/// JSON too. Returns the paths of the replaced strings, or the error of a string that would nest
+/// the document more than `max_depth` levels deep.
+pub fn parse_nested(
+    json: &mut Value,
+    max_depth: Option<usize>,
+) -> serde_json::Result<Vec<Vec<MatchNode>>> {
+    let mut nested = Vec::new();
+    let mut path: Vec<MatchNode> = Vec::new();
+    // Every value, with the length of its path and the last node of it
+    let mut stack: Vec<(&mut Value, usize, Option<MatchNode>)> = vec![(json, 0, None)];
+    while let Some((value, depth, node)) = stack.pop() {
+        path.truncate(depth.saturating_sub(1));
+        path.extend(node);
+
+        if let Value::String(s) = value {
+            let trimmed = s.trim_start();
+            if trimmed.starts_with('{') || trimmed.starts_with('[') {
+                match parse(s, max_depth.map(|max| max.saturating_sub(depth))) {
+                    Ok(parsed @ (Value::Object(_) | Value::Array(_))) => {
+                        *value = parsed;
+                        nested.push(path.clone());
+                    }
+                    // Nested too deep, the rest is just not JSON
+                    Err(err) if err.is_data() => return Err(err),
+                    _ => {}
+                }
+            }
+        }
+        match value {
+            Value::Array(vec) => stack.extend(
+                vec.iter_mut()
+                    .enumerate()
+                    .rev()
+                    .map(|(i, v)| (v, depth + 1, Some(MatchNode::new_index(i, false)))),
+            ),
+            Value::Object(map) => stack.extend(
+                map.iter_mut()
+                    .rev()
+                    .map(|(k, v)| (v, depth + 1, Some(MatchNode::new_key(k.clone(), false)))),
+            ),
+            _ => {}
+        }
+    }
+    Ok(nested)
+}
+
+enum Token<'a> {
+    Value(&'a Value),
+    Key(&'a str),
+    Str(&'static str),
+}
+
+/// Writes the value as compact JSON, the same way its `Display` implementation does, but without
+/// recursion.
+pub fn write_compact<W: Write>(writer: &mut W, value: &Value) -> io::Result<()> {
+    let mut stack = vec![Token::Value(value)];
+    while let Some(token) = stack.pop() {
+        match token {
+            Token::Value(Value::Array(vec)) => {
+                write!(writer, "[")?;
+                stack.push(Token::Str("]"));
+                for (i, v) in vec.iter().enumerate().rev() {
+                    stack.push(Token::Value(v));
+                    if i > 0 {
+                        stack.push(Token::Str(","));
+                    }
+                }
+            }
+            Token::Value(Value::Object(map)) => {
+                write!(writer, "{{")?;
+                stack.push(Token::Str("}"));
+                for (i, (k, v)) in map.iter().enumerate().rev() {
+                    stack.push(Token::Value(v));
+                    stack.push(Token::Key(k));
+                    if i > 0 {
+                        stack.push(Token::Str(","));
+                    }
+                }
+            }
+            Token::Value(scalar) => write!(writer, "{}", scalar)?,
+            Token::Key(k) => write!(writer, "{}:", Value::String(k.to_string()))?,
+            Token::Str(s) => write!(writer, "{}", s)?,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{depth, drop_deep, parse, parse_nested, write_compact};
+    use crate::matcher::match_node::MatchNode;
+
+    #[test]
+    fn test_write_compact() {
+        let value = json!({ "a": [1, "two", { "\"three\"": null }, []], "b": {}, "c": true });
+
+        let mut output = Vec::new();
+        write_compact(&mut output, &value).unwrap();
+
+        assert_eq!(String::from_utf8(output).unwrap(), value.to_string());
+    }
+
+    #[test]
+    fn test_deep() {
+        let levels = 100_000;
+        let content = format!("{}{}", "[".repeat(levels), "]".repeat(levels));
+
+        let value = parse(&content, None).unwrap();
+        assert_eq!(depth(&value), levels);
+
+        let mut output = Vec::new();
+        write_compact(&mut output, &value).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), content);
+        drop_deep(value);
+    }
+
+    #[test]
+    fn test_max_depth() {
+        for content in ["[[1.5]]", "[{\"a\": 123456789012345678901234567890}]", "[[]]", "[{}]"] {
+            assert!(parse(content, Some(2)).is_ok(), "{}", content);
+        }
+        for content in ["[[[1]]]", "[[[]]]", "[{\"a\": {}}]", "[[{\"a\": 1}]]"] {
+            let err = parse(content, Some(2)).unwrap_err();
+            assert!(err.to_string().starts_with("JSON nested deeper than the maximum depth of 2"));
+        }
+
+        let mut value = json!([["[1]"], "[[1]]"]);
+        assert!(parse_nested(&mut value, Some(3)).is_ok());
+        let mut value = json!([["[[1]]"]]);
+        assert!(parse_nested(&mut value, Some(3)).is_err());
+    }
+
+    #[test]
+    fn test_parse_nested() {
+        let mut value = json!({
+            "body": "{\"user\": {\"id\": 7}, \"raw\": \"[1, 2]\"}",
+            "items": [" [3]", "{not json", "12"],
+        });
+
+        let nested = parse_nested(&mut value, None).unwrap();
+
+        assert_eq!(
+            value,
+            json!({
+                "body": { "user": { "id": 7 }, "raw": [1, 2] },
+                "items": [[3], "{not json", "12"],
+            })
+        );
+        let key = |k: &str| MatchNode::new_key(k.to_string(), false);
+        assert_eq!(
+            nested,
+            vec![
+                vec![key("body")],
+                vec![key("body"), key("raw")],
+                vec![key("items"), MatchNode::new_index(0, false)],
+            ]
+        );
+    }
+}
@@ -1,8 +1,8 @@
-use std::cmp::Ordering;
+use std::{cmp::Ordering, collections::HashMap};
 
 use itertools::Itertools;
 
-use crate::pattern::pattern_node::PatternNode;
+use crate::{matcher::match_node::MatchNode, pattern::pattern_node::PatternNode};
 
 
 pub fn sort_matches(matches: &mut [Vec<PatternNode>]) {
@@ -45,9 +45,139 @@ pub fn sort_matches(matches: &mut [Vec<PatternNode>]) {
     });
 }
 
+/// A trie of the positions of all the matches, ignoring which nodes are highlighted.
+struct MatchTrie<'a> {
+    children: Vec<HashMap<PathKey<'a>, usize>>,
+}
+
+#[derive(PartialEq, Eq, Hash)]
+enum PathKey<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+impl<'a> MatchTrie<'a> {
+    fn new(matches: &'a [Vec<MatchNode>]) -> Self {
+        let mut trie = MatchTrie {
+            children: vec![HashMap::new()],
+        };
+        for m in matches {
+            trie.insert(m);
+        }
+        trie
+    }
+
+    fn key(node: &MatchNode) -> PathKey<'_> {
+        match node {
+            MatchNode::Key(k) => PathKey::Key(&k.key),
+            MatchNode::Index(i) => PathKey::Index(i.index),
+        }
+    }
+
+    fn insert(&mut self, m: &'a [MatchNode]) {
+        let mut current = 0;
+        for node in m {
+            let next = self.children.len();
+            current = *self.children[current]
+                .entry(Self::key(node))
+                .or_insert(next);
+            if current == next {
+                self.children.push(HashMap::new());
+            }
+        }
+    }
+
+    /// The trie nodes along the path of a match, starting after the root.
+    fn path(&self, m: &'a [MatchNode]) -> Vec<usize> {
+        let mut current = 0;
+        m.iter()
+            .map(|node| {
+                current = self.children[current][&Self::key(node)];
+                current
+            })
+            .collect()
+    }
+}
+
+/// Keeps only the matches that aren't inside another match. When the same value matches more than
+/// once, only the first match is kept.
+pub fn outermost_matches(matches: Vec<Vec<MatchNode>>) -> Vec<Vec<MatchNode>> {
+    let trie = MatchTrie::new(&matches);
+    let mut matched = vec![false; trie.children.len()];
+    for m in &matches {
+        matched[trie.path(m).last().copied().unwrap_or(0)] = true;
+    }
+
+    let mut seen = vec![false; trie.children.len()];
+    let keep: Vec<bool> = matches
+        .iter()
+        .map(|m| {
+            let path = trie.path(m);
+            let end = path.last().copied().unwrap_or(0);
+            let inside_match = !m.is_empty()
+                && (matched[0] || path[..path.len() - 1].iter().any(|&n| matched[n]));
+            !inside_match && !std::mem::replace(&mut seen[end], true)
+        })
+        .collect();
+
+    matches
+        .into_iter()
+        .zip(keep)
+        .filter_map(|(m, keep)| keep.then_some(m))
+        .collect()
+}
+
+/// Keeps only the matches that don't contain another match. When the same value matches more than
+/// once, only the first match is kept.
+pub fn innermost_matches(matches: Vec<Vec<MatchNode>>) -> Vec<Vec<MatchNode>> {
+    let trie = MatchTrie::new(&matches);
+
+    // Every path in the trie ends in a match, so a node contains another match if it has children
+    let mut seen = vec![false; trie.children.len()];
+    let keep: Vec<bool> = matches
+        .iter()
+        .map(|m| {
+            let end = trie.path(m).last().copied().unwrap_or(0);
+            trie.children[end].is_empty() && !std::mem::replace(&mut seen[end], true)
+        })
+        .collect();
+
+    matches
+        .into_iter()
+        .zip(keep)
+        .filter_map(|(m, keep)| keep.then_some(m))
+        .collect()
+}
+
+/// Marks the nodes of the matches whose value was a string holding JSON, parsed at one of the
+/// `nested` paths.
+pub fn mark_nested(matches: &mut [Vec<MatchNode>], nested: &[Vec<MatchNode>]) {
+    if nested.is_empty() {
+        return;
+    }
+    let trie = MatchTrie::new(nested);
+    let mut is_nested = vec![false; trie.children.len()];
+    for path in nested {
+        is_nested[trie.path(path).last().copied().unwrap_or(0)] = true;
+    }
+
+    for m in matches {
+        let mut current = 0;
+        for node in m.iter_mut() {
+            match trie.children[current].get(&MatchTrie::key(node)) {
+                Some(&next) => current = next,
+                None => break,
+            }
+            if is_nested[current] {
+                node.set_nested();
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::pattern::pattern_node::PatternNode;
+    use crate::{matcher::match_node::MatchNode, pattern::pattern_node::PatternNode};
 
     use super::sort_matches;
 
@@ -102,4 +232,51 @@ mod tests {
         sort_matches(&mut matches);
         assert_eq!(sorted, matches);
     }
+
+    #[test]
+    fn test_outermost_and_innermost() {
+        let a = MatchNode::new_key("a".to_string(), false);
+        let b = MatchNode::new_key("b".to_string(), true);
+        let c = MatchNode::new_key("c".to_string(), true);
+        let i = MatchNode::new_index(0, true);
+
+        let matches = vec![
+            vec![a.clone(), b.clone(), c.clone()],
+            vec![a.clone(), b.clone()],
+            vec![MatchNode::new_key("a".to_string(), true), b.clone()],
+            vec![a.clone(), c.clone(), i.clone()],
+            vec![c.clone()],
+        ];
+
+        assert_eq!(
+            super::outermost_matches(matches.clone()),
+            vec![
+                vec![a.clone(), b.clone()],
+                vec![a.clone(), c.clone(), i.clone()],
+                vec![c.clone()],
+            ]
+        );
+
+        assert_eq!(
+            super::innermost_matches(matches),
+            vec![
+                vec![a.clone(), b.clone(), c.clone()],
+                vec![a.clone(), c.clone(), i.clone()],
+                vec![c.clone()],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mark_nested() {
+        let body = MatchNode::new_key("body".to_string(), false);
+        let user = MatchNode::new_key("user".to_string(), true);
+        let mut matches = vec![vec![body.clone(), user.clone()], vec![user.clone()]];
+
+        super::mark_nested(&mut matches, &[vec![body.clone()]]);
+
+        let mut nested_body = body.clone();
+        nested_body.set_nested();
+        assert_eq!(matches, vec![vec![nested_body, user.clone()], vec![user]]);
+    }
 }
@@ -0,0 +1,52 @@
+use std::io::Write;
+
+use assert_cmd::Command;
+use common::test_dir::TestDir;
+use flate2::{write::GzEncoder, Compression};
+
+mod common {
+    pub mod test_dir;
+}
+
+fn tar(files: &[(&str, &str)]) -> Vec<u8> {
+    let mut builder = tar::Builder::new(Vec::new());
+    for (path, content) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        builder.append_data(&mut header, path, content.as_bytes()).unwrap();
+    }
+    builder.into_inner().unwrap()
+}
+
+#[test]
+fn compressed_tar() {
+    let files = [
+        ("logs/a.json", "{\"id\": 1}"),
+        ("logs/notes.txt", "not json"),
+        ("logs/b.ndjson", "{\"id\": 2}\n{\"id\": 3}"),
+    ];
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&tar(&files)).unwrap();
+    let dir = TestDir::new("archive");
+    dir.write("bundle.tar.gz", encoder.finish().unwrap());
+
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.current_dir(&dir);
+    cmd.args([".id", "bundle.tar.gz"]);
+
+    cmd.assert().code(0).stdout(
+        "bundle.tar.gz:logs/a.json:.id: 1\n\
+         bundle.tar.gz:logs/b.ndjson:.id: 2\n\
+         bundle.tar.gz:logs/b.ndjson:.id: 3\n",
+    );
+}
+
+#[test]
+fn tar_on_stdin() {
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.args([".id", "-l", "--include", "*.txt"]);
+    cmd.write_stdin(tar(&[("a.txt", "{\"id\": 1}"), ("b.txt", "{}")]));
+
+    cmd.assert().code(0).stdout("(standard input):a.txt\n");
+}
@@ -0,0 +1,58 @@
+use assert_cmd::Command;
+
+// {"id": h'dead', "n": -1} followed by [1.5]
+const CBOR: &[u8] = &[
+    0xa2, 0x62, b'i', b'd', 0x42, 0xde, 0xad, 0x61, b'n', 0x20, 0x81, 0xf9, 0x3e, 0x00,
+];
+
+// {"id": bin 'dead'} followed by 7
+const MSGPACK: &[u8] = &[0x81, 0xa2, b'i', b'd', 0xc4, 0x02, 0xde, 0xad, 0x07];
+
+#[test]
+fn cbor_stream() {
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.args(["[0]", "--input", "cbor"]);
+    cmd.write_stdin(CBOR);
+    cmd.assert().code(0).stdout("[0]: 1.5\n");
+
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.args([".id", "--input", "cbor", "--bytes", "hex"]);
+    cmd.write_stdin(CBOR);
+    cmd.assert().code(0).stdout(".id: \"dead\"\n");
+}
+
+#[test]
+fn msgpack_stream() {
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.args(["", "--input", "msgpack", "-o"]);
+    cmd.write_stdin(MSGPACK);
+    cmd.assert()
+        .code(0)
+        .stdout("{\"id\":\"3q0=\"}\n\"3q0=\"\n7\n");
+}
+
+#[test]
+fn invalid_items() {
+    // An item cut short after the first one, then a reserved header byte
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.args([".n", "--input", "cbor"]);
+    cmd.write_stdin(&CBOR[..11]);
+    cmd.assert()
+        .code(3)
+        .stdout(".n: -1\n")
+        .stderr("(standard input): offset 11: Invalid CBOR: unexpected end of input\n");
+
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.args(["", "--input", "cbor", "-c"]);
+    cmd.write_stdin([&CBOR[..10], &[0x1c]].concat());
+    cmd.assert()
+        .code(3)
+        .stderr("(standard input): offset 10: Invalid CBOR: syntax error\n");
+
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.args([".id", "--input", "msgpack"]);
+    cmd.write_stdin(&MSGPACK[..7]);
+    cmd.assert()
+        .code(3)
+        .stderr("(standard input): offset 7: Invalid MessagePack: unexpected end of input\n");
+}
@@ -0,0 +1,64 @@
+use std::{
+    ffi::OsStr,
+    fs,
+    ops::Deref,
+    path::{Path, PathBuf},
+};
+
+// A temporary directory unique to the test, removed when it is dropped, even if the test fails
+pub struct TestDir(PathBuf);
+
+impl TestDir {
+    #[allow(unused)]
+    pub fn new(name: &str) -> TestDir {
+        let dir = std::env::temp_dir().join(format!("jgrep-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        TestDir(dir)
+    }
+
+    // A directory holding the files, given by their path in it and their content
+    #[allow(unused)]
+    pub fn with_files(name: &str, files: &[(&str, &str)]) -> TestDir {
+        let dir = TestDir::new(name);
+        for (path, content) in files {
+            dir.write(path, content);
+        }
+        dir
+    }
+
+    // Writes a file, creating the directories it is in, and returns its path
+    #[allow(unused)]
+    pub fn write(&self, name: &str, content: impl AsRef<[u8]>) -> PathBuf {
+        let path = self.0.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, content).unwrap();
+        path
+    }
+}
+
+impl Deref for TestDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TestDir {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<OsStr> for TestDir {
+    fn as_ref(&self) -> &OsStr {
+        self.0.as_os_str()
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
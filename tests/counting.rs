@@ -0,0 +1,75 @@
+use assert_cmd::Command;
+use indoc::indoc;
+
+const DOCUMENTS: &str = indoc!(r#"
+    {"id": 1, "tags": ["a", "b"]}
+    {"id": 2, "tags": ["c"]}
+    {"id": 3, "tags": ["d", "e"]}
+    invalid
+"#);
+
+#[test]
+fn max_count() {
+    let out = indoc!(r#"
+        .tags[0]: "a"
+        .tags[1]: "b"
+        .tags[0]: "c"
+    "#);
+
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.arg(".tags[]");
+    cmd.arg("-m");
+    cmd.arg("3");
+    cmd.write_stdin(DOCUMENTS);
+
+    cmd.assert().code(0).stdout(out);
+}
+
+#[test]
+fn max_count_per_document() {
+    let out = indoc!(r#"
+        .tags[0]: "a"
+        .tags[0]: "c"
+        .tags[0]: "d"
+    "#);
+
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.arg(".tags[]");
+    cmd.arg("-m");
+    cmd.arg("1");
+    cmd.arg("--count-per");
+    cmd.arg("document");
+    cmd.write_stdin(&DOCUMENTS[..DOCUMENTS.find("invalid").unwrap()]);
+
+    cmd.assert().code(0).stdout(out);
+}
+
+#[test]
+fn count() {
+    let documents = &DOCUMENTS[..DOCUMENTS.find("invalid").unwrap()];
+
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.arg(".tags[]");
+    cmd.arg("-c");
+    cmd.write_stdin(documents);
+
+    cmd.assert().code(0).stdout("5\n");
+
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.arg(".tags[]");
+    cmd.arg("-c");
+    cmd.arg("--count-per");
+    cmd.arg("document");
+    cmd.write_stdin(documents);
+
+    cmd.assert().code(0).stdout("2\n1\n2\n");
+
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.arg(".tags[]");
+    cmd.arg("-c");
+    cmd.arg("-m");
+    cmd.arg("4");
+    cmd.write_stdin(DOCUMENTS);
+
+    cmd.assert().code(0).stdout("4\n");
+}
@@ -0,0 +1,51 @@
+use std::io::Write;
+
+use assert_cmd::Command;
+use common::test_dir::TestDir;
+use flate2::{write::GzEncoder, Compression};
+
+mod common {
+    pub mod test_dir;
+}
+
+fn gzip(data: &str) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data.as_bytes()).unwrap();
+    encoder.finish().unwrap()
+}
+
+#[test]
+fn compressed_file() {
+    let dir = TestDir::new("decompress");
+    let path = dir.write("lines.ndjson.gz", gzip("{\"id\": 1}\n{\"id\": 2}\n"));
+
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.args([".id", "--ndjson", "-H"]);
+    cmd.arg(&path);
+
+    let name = path.display().to_string();
+    cmd.assert()
+        .code(0)
+        .stdout(format!("{name}:1:.id: 1\n{name}:2:.id: 2\n"));
+}
+
+#[test]
+fn compressed_stdin() {
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.args([".id", "--stream"]);
+    cmd.write_stdin(gzip("{\"id\": 1}"));
+
+    cmd.assert().code(0).stdout(".id: 1\n");
+}
+
+#[test]
+fn force_decompress() {
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.args([".id", "-z"]);
+    cmd.write_stdin("{\"id\": 1}");
+
+    cmd.assert()
+        .code(2)
+        .stdout("")
+        .stderr("(standard input): Not in a supported compressed format\n");
+}
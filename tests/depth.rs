@@ -0,0 +1,52 @@
+use assert_cmd::Command;
+
+fn nested(levels: usize) -> String {
+    format!("{}1{}", r#"{"a":"#.repeat(levels), "}".repeat(levels))
+}
+
+#[test]
+fn deeply_nested() {
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.arg(": 1");
+    cmd.arg("-o");
+    cmd.write_stdin(nested(20_000));
+
+    cmd.assert().code(0).stdout("1\n");
+}
+
+#[test]
+fn max_depth_guard() {
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.arg(": 1");
+    cmd.arg("--max-depth-guard");
+    cmd.arg("100");
+    cmd.write_stdin(nested(101));
+
+    cmd.assert().code(3).stdout("");
+
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.arg(": 1");
+    cmd.arg("-o");
+    cmd.arg("--max-depth-guard");
+    cmd.arg("100");
+    cmd.write_stdin(nested(100));
+
+    cmd.assert().code(0).stdout("1\n");
+}
+
+#[test]
+fn max_depth_guard_while_parsing() {
+    // The document is rejected as soon as it is parsed too deep, without building the rest of it.
+    // Lines of JSON are skipped instead.
+    for (args, code) in [(vec![], 3), (vec!["--ndjson"], 2)] {
+        let mut cmd = Command::cargo_bin("jgrep").unwrap();
+        cmd.args([": 1", "--max-depth-guard", "100"]);
+        cmd.args(args);
+        cmd.write_stdin(format!("{}{}", "[".repeat(1_000_000), "]".repeat(1_000_000)));
+
+        let assert = cmd.assert().code(code).stdout("");
+        let stderr = String::from_utf8_lossy(&assert.get_output().stderr);
+        assert!(stderr.starts_with("(standard input): line 1, column 10"));
+        assert!(stderr.contains("Invalid JSON: JSON nested deeper than the maximum depth of 100"));
+    }
+}
@@ -0,0 +1,65 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    process::{self, Stdio},
+    sync::mpsc,
+    time::Duration,
+};
+
+use assert_cmd::{cargo::cargo_bin, Command};
+
+const LOG: &str = "\
+2026-10-01 12:00:01 INFO {\"event\":\"login\",\"user\":{\"id\":7}}
+Starting worker [pid 12]
+2026-10-01 12:00:02 WARN retry [1,2] then {\"event\":\"logout\",\"user\":{\"id\":8}}
+";
+
+#[test]
+fn extract() {
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.args([".user.id", "--extract"]);
+    cmd.write_stdin(LOG);
+
+    cmd.assert().code(0).stdout(
+        "1:2026-10-01 12:00:01 INFO .user.id: 7\n\
+         3:2026-10-01 12:00:02 WARN retry [1,2] then .user.id: 8\n",
+    );
+}
+
+#[test]
+fn extract_only() {
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.args([".event", "--extract", "-o"]);
+    cmd.write_stdin(LOG);
+
+    cmd.assert().code(0).stdout(
+        "1:2026-10-01 12:00:01 INFO \"login\"\n\
+         3:2026-10-01 12:00:02 WARN retry [1,2] then \"logout\"\n",
+    );
+}
+
+#[test]
+fn extract_live_stdin() {
+    // The first line is searched before the next one comes, even if it is shorter than a tar header
+    let mut child = process::Command::new(cargo_bin("jgrep"))
+        .args([".user.id", "--extract", "-o"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    stdin.write_all(b"INFO {\"user\":{\"id\":7}}\n").unwrap();
+    stdin.flush().unwrap();
+
+    let stdout = child.stdout.take().unwrap();
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut line = String::new();
+        BufReader::new(stdout).read_line(&mut line).unwrap();
+        sender.send(line).unwrap();
+    });
+    let line = receiver.recv_timeout(Duration::from_secs(10));
+
+    drop(stdin);
+    child.wait().unwrap();
+    assert_eq!(line.as_deref(), Ok("1:INFO 7\n"));
+}
@@ -0,0 +1,119 @@
+use assert_cmd::Command;
+use common::{json_constants, test_dir::TestDir};
+
+mod common {
+    pub mod json_constants;
+    pub mod test_dir;
+}
+
+// Some JSON files, for a test directory
+const FILES: &[(&str, &str)] = &[
+    ("a.json", r#"{"id": 1}"#),
+    ("sub/b.json", r#"{"id": 2}"#),
+    ("sub/c.txt", r#"{"id": 3}"#),
+    ("README.md", "# Not JSON\n"),
+    ("ignored.json", r#"{"id": 4}"#),
+    (".gitignore", "ignored.json\n"),
+];
+
+#[test]
+fn multiple_files() {
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.arg("Jane");
+    cmd.arg("example.json");
+    cmd.arg("-");
+    cmd.write_stdin(json_constants::README_EXAMPLE);
+
+    cmd.assert().code(0).stdout(
+        "example.json:.items[1].meta.author.name: \"Jane\"\n\
+         (standard input):.items[1].meta.author.name: \"Jane\"\n",
+    );
+}
+
+#[test]
+fn missing_file() {
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.arg("Jane");
+    cmd.arg("missing.json");
+    cmd.arg("example.json");
+
+    cmd.assert()
+        .code(2)
+        .stdout("example.json:.items[1].meta.author.name: \"Jane\"\n")
+        .stderr("missing.json: No such file or directory\n");
+}
+
+#[test]
+fn recursive() {
+    let dir = TestDir::with_files("recursive", FILES);
+
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.current_dir(&dir);
+    cmd.arg(".id");
+    cmd.arg("-r");
+
+    cmd.assert()
+        .code(0)
+        .stdout("a.json:.id: 1\nsub/b.json:.id: 2\n");
+
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.current_dir(&dir);
+    cmd.args([".id", "-r", "--include", "*.txt"]);
+
+    cmd.assert().code(0).stdout("sub/c.txt:.id: 3\n");
+
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.current_dir(&dir);
+    cmd.arg(".id");
+    cmd.args(["-r", "--include", "*.json", "--exclude", "a*", "--no-ignore", "."]);
+
+    cmd.assert()
+        .code(0)
+        .stdout("./ignored.json:.id: 4\n./sub/b.json:.id: 2\n");
+}
+
+#[test]
+fn mapped_files() {
+    let dir = TestDir::with_files("mapped", FILES);
+    dir.write("empty.json", "");
+    dir.write("lines.jsonl", "{\"id\": 5}\n\n{\"id\": 6}");
+
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.current_dir(&dir);
+    cmd.args([".id", "empty.json", "a.json", "lines.jsonl", "--ndjson"]);
+
+    cmd.assert()
+        .code(0)
+        .stdout("a.json:1:.id: 1\nlines.jsonl:1:.id: 5\nlines.jsonl:3:.id: 6\n");
+}
+
+#[test]
+fn many_files_on_several_threads() {
+    let dir = TestDir::with_files("many-files", FILES);
+    for i in 0..200 {
+        let document = format!("[{{\"id\": {i}}}, {{\"id\": {i}}}]\n");
+        dir.write(&format!("sub/many-{i:03}.json"), document);
+    }
+    let expected: String = (0..200).map(|i| format!("{i}\n{i}\n")).collect();
+
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.args([".id", "-r", "-o", "-J", "4", "--no-filename", "--include", "many-*"]);
+    cmd.arg(&dir);
+    cmd.assert().code(0).stdout(expected);
+
+    // The rest of every file is dropped once its output is complete
+    let expected: String = (0..200).map(|i| format!("{i}\n")).collect();
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.args([".id", "-r", "-o", "-J", "4", "-m", "1", "--no-filename", "--include", "many-*"]);
+    cmd.arg(&dir);
+    cmd.assert().code(0).stdout(expected);
+
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.args([".id", "-r", "-l", "-J", "4", "--include", "many-00*"]);
+    cmd.arg(&dir);
+    let sub = dir.join("sub");
+    let expected: String = (0..10)
+        .map(|i| format!("{}\n", sub.join(format!("many-{i:03}.json")).display()))
+        .collect();
+    cmd.assert().code(0).stdout(expected);
+}
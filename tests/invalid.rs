@@ -0,0 +1,68 @@
+use assert_cmd::Command;
+use common::test_dir::TestDir;
+
+mod common {
+    pub mod test_dir;
+}
+
+// A broken file between valid ones, for a test directory
+const FILES: &[(&str, &str)] = &[
+    ("a.json", "{\"id\": 1}\n"),
+    ("b.json", "{\n  \"id\": 2,\n  \"name\": \"x\"\n  \"tags\": []\n}\n"),
+    ("c.yaml", "id: [3\n"),
+    ("d.json", "{\"id\": 4}\n"),
+];
+
+#[test]
+fn error_position() {
+    let dir = TestDir::with_files("error-position", FILES);
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.arg(".id");
+    cmd.arg(dir.join("b.json"));
+
+    cmd.assert().code(3).stdout("").stderr(format!(
+        "{}: line 4, column 3: Invalid JSON: expected `,` or `}}`\n    \
+         \"tags\": []\n    ^\n",
+        dir.join("b.json").display()
+    ));
+}
+
+#[test]
+fn stops_at_invalid_file() {
+    let dir = TestDir::with_files("stops-at-invalid", FILES);
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.args([".id", "-r", "-o", "--no-filename"]);
+    cmd.arg(&dir);
+
+    cmd.assert().code(3).stdout("1\n");
+}
+
+#[test]
+fn skip_invalid_files() {
+    let dir = TestDir::with_files("skip-invalid-files", FILES);
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.args([".id", "-r", "-o", "--no-filename", "--skip-invalid"]);
+    cmd.arg(&dir);
+
+    cmd.assert().code(2).stdout("1\n4\n").stderr(format!(
+        "{}: line 4, column 3: Invalid JSON: expected `,` or `}}`\n    \
+         \"tags\": []\n    ^\n\
+         {}: line 2, column 1: Invalid YAML: did not find expected ',' or ']'\n",
+        dir.join("b.json").display(),
+        dir.join("c.yaml").display()
+    ));
+}
+
+#[test]
+fn skip_invalid_documents() {
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.args([".id", "-o", "--skip-invalid"]);
+    cmd.write_stdin("{\"id\": 1}\n{\"id\": 2,,}\n{\"id\":\n  3} {\"id\": 4}\n[5}\n{\"id\": 6}\n");
+
+    cmd.assert().code(2).stdout("1\n3\n4\n6\n").stderr(
+        "(standard input): line 2, column 10: Invalid JSON: key must be a string\n  \
+         {\"id\": 2,,}\n           ^\n\
+         (standard input): line 5, column 3: Invalid JSON: expected `,` or `]`\n  \
+         [5}\n    ^\n",
+    );
+}
@@ -0,0 +1,94 @@
+use assert_cmd::Command;
+use common::json_constants;
+use indoc::indoc;
+
+mod common {
+    pub mod json_constants;
+}
+
+#[test]
+fn invert_document() {
+    let out = indoc!(r#"
+    {
+      "items": [
+        {
+          "id": 1,
+          "name": "Lorem",
+          "active": true
+        },
+        {
+          "id": 2,
+          "name": "Ipsum",
+          "active": false
+        }
+      ]
+    }
+    "#);
+
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.arg(".meta");
+    cmd.arg("-v");
+    cmd.write_stdin(json_constants::README_EXAMPLE);
+
+    cmd.assert().code(0).stdout(out);
+}
+
+#[test]
+fn invert_stream() {
+    let out = indoc!(r#"
+        {"level":"info","message":"started"}
+        [1,2]
+    "#);
+
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.arg("*debug*");
+    cmd.arg("-v");
+    cmd.write_stdin(indoc!(r#"
+        {"level": "info", "message": "started"}
+        {"level": "debug", "message": "connected"}
+        [1, 2]
+        {"debug": {"id": 3}}
+    "#));
+
+    cmd.assert().code(0).stdout(out);
+}
+
+#[test]
+fn invert_escaped_strings() {
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.args([".id", "-v"]);
+    cmd.write_stdin(r#"{"id": 1, "text": "say \"hi\"\n\tbye\\"}"#);
+
+    cmd.assert()
+        .code(0)
+        .stdout("{\n  \"text\": \"say \\\"hi\\\"\\n\\tbye\\\\\"\n}\n");
+}
+
+#[test]
+fn invert_single_line() {
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.args([".id", "-v", "--ndjson"]);
+    cmd.write_stdin("{\"name\": \"a\", \"tags\": [1]}\n");
+
+    cmd.assert()
+        .code(0)
+        .stdout("1:{\"name\":\"a\",\"tags\":[1]}\n");
+}
+
+#[test]
+fn invert_escaped_keys() {
+    let input = r#"{"a\"b\\c\u0001": 1, "x": 2}"#;
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.args([".x", "-v"]);
+    cmd.write_stdin(input);
+    cmd.assert()
+        .code(0)
+        .stdout("{\n  \"a\\\"b\\\\c\\u0001\": 1\n}\n");
+
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.args([": 1", "-j"]);
+    cmd.write_stdin(input);
+    cmd.assert()
+        .code(0)
+        .stdout("{\n  \"a\\\"b\\\\c\\u0001\": 1\n}\n");
+}
@@ -0,0 +1,61 @@
+use assert_cmd::Command;
+use common::test_dir::TestDir;
+
+mod common {
+    pub mod test_dir;
+}
+
+const CONFIG: &str = r#"{
+    // Type checking
+    "compilerOptions": {
+        "strict": true, /* everywhere */
+        target: 'es2022',
+    },
+}
+"#;
+
+#[test]
+fn jsonc_flag() {
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.args([".target", "--jsonc"]);
+    cmd.write_stdin(CONFIG);
+    cmd.assert()
+        .code(0)
+        .stdout(".compilerOptions.target: \"es2022\"\n");
+
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.args([".target"]);
+    cmd.write_stdin(CONFIG);
+    cmd.assert().code(3).stderr(
+        "(standard input): line 2, column 5: Invalid JSON: key must be a string\n      \
+         // Type checking\n      ^\n",
+    );
+}
+
+#[test]
+fn jsonc_config_names() {
+    let dir = TestDir::new("jsonc");
+    let tsconfig = dir.write("tsconfig.json", CONFIG);
+    let settings = dir.write(".vscode/settings.json", "{\"editor.tabSize\": 4, // spaces\n}");
+
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.args([".strict", "--no-filename"]);
+    cmd.arg(tsconfig);
+    cmd.assert()
+        .code(0)
+        .stdout(".compilerOptions.strict: true\n");
+
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.arg("tabSize");
+    cmd.arg(settings);
+    cmd.assert().code(0).stdout(".editor.tabSize: 4\n");
+}
+
+#[test]
+fn jsonc_valid_json() {
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.args([": 1.50", "--jsonc"]);
+    cmd.write_stdin("{\"version\": 1.50, \"id\": 12345678901234567890}");
+
+    cmd.assert().code(0).stdout(".version: 1.50\n");
+}
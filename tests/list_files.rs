@@ -0,0 +1,33 @@
+use assert_cmd::Command;
+use common::json_constants;
+
+mod common {
+    pub mod json_constants;
+}
+
+#[test]
+fn files_with_matches() {
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.arg("Jane");
+    cmd.arg("-l");
+    cmd.arg("example.json");
+
+    cmd.assert().code(0).stdout("example.json\n");
+
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.arg("Janet");
+    cmd.arg("-l");
+    cmd.arg("example.json");
+
+    cmd.assert().code(0).stdout("");
+}
+
+#[test]
+fn files_without_match() {
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.arg("Janet");
+    cmd.arg("-L");
+    cmd.write_stdin(json_constants::README_EXAMPLE);
+
+    cmd.assert().code(0).stdout("(standard input)\n");
+}
@@ -0,0 +1,41 @@
+use assert_cmd::Command;
+
+const LOG: &str = "{\"user\": {\"id\": 7}}\n\n{\"user\": }\n{\"user\": {\"id\": 8}}\n";
+
+#[test]
+fn line_numbers() {
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.arg(".user.id");
+    cmd.arg("--ndjson");
+    cmd.write_stdin(LOG);
+
+    cmd.assert()
+        .code(2)
+        .stdout("1:.user.id: 7\n4:.user.id: 8\n")
+        .stderr(
+            "(standard input): line 3, column 10: Invalid JSON: expected value\n  \
+             {\"user\": }\n           ^\n",
+        );
+}
+
+#[test]
+fn count_per_line() {
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.arg(".id");
+    cmd.args(["--jsonl", "-c", "--count-per", "document"]);
+    cmd.write_stdin(LOG);
+
+    cmd.assert().code(2).stdout("1:1\n4:1\n");
+}
+
+#[test]
+fn indented_line_error() {
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.args([".id", "--ndjson"]);
+    cmd.write_stdin("  {\"id\": 1}\n    {\"id\": }\n");
+
+    cmd.assert().code(2).stdout("1:.id: 1\n").stderr(
+        "(standard input): line 2, column 12: Invalid JSON: expected value\n      \
+         {\"id\": }\n             ^\n",
+    );
+}
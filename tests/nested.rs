@@ -0,0 +1,26 @@
+use assert_cmd::Command;
+
+const EVENT: &str = r#"{"id": 1, "body": "{\"user\": {\"id\": 7}, \"tags\": \"[\\\"a\\\"]\"}"}"#;
+
+#[test]
+fn parse_nested() {
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.args([".user.id", "--parse-nested"]);
+    cmd.write_stdin(EVENT);
+    cmd.assert().code(0).stdout(".body<json>.user.id: 7\n");
+
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.args(["[0]", "--parse-nested"]);
+    cmd.write_stdin(EVENT);
+    cmd.assert()
+        .code(0)
+        .stdout(".body<json>.tags<json>[0]: \"a\"\n");
+}
+
+#[test]
+fn strings_without_parse_nested() {
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.arg(".user.id");
+    cmd.write_stdin(EVENT);
+    cmd.assert().code(0).stdout("");
+}
@@ -0,0 +1,25 @@
+use assert_cmd::Command;
+
+const NESTED: &str = r#"{"a": {"a": 1}, "b": {"a": 2}}"#;
+
+#[test]
+fn outermost() {
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.arg(".a");
+    cmd.arg("--outermost");
+    cmd.write_stdin(NESTED);
+
+    cmd.assert().code(0).stdout(".a: {\"a\":1}\n.b.a: 2\n");
+}
+
+#[test]
+fn innermost() {
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.arg(".a");
+    cmd.arg("--innermost");
+    cmd.arg("-m");
+    cmd.arg("1");
+    cmd.write_stdin(NESTED);
+
+    cmd.assert().code(0).stdout(".a.a: 1\n");
+}
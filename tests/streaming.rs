@@ -0,0 +1,99 @@
+use assert_cmd::Command;
+use common::test_dir::TestDir;
+
+mod common {
+    pub mod test_dir;
+}
+
+#[test]
+fn brackets_in_strings() {
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.arg(".a");
+    cmd.write_stdin("{\"a\": \"}{\"}\n{\"a\": \"[ñ]\"}{\"a\": 3}");
+
+    cmd.assert()
+        .code(0)
+        .stdout(".a: \"}{\"\n.a: \"[ñ]\"\n.a: 3\n");
+}
+
+#[test]
+fn scalar_documents() {
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.arg(": lorem*");
+    cmd.write_stdin("\"lorem ipsum\" 42 null {\"k\": \"lorem\"}");
+
+    cmd.assert()
+        .code(0)
+        .stdout(".: \"lorem ipsum\"\n.k: \"lorem\"\n");
+}
+
+#[test]
+fn concatenated_file() {
+    let dir = TestDir::new("concatenated");
+    let path = dir.write("concatenated.json", "{\"a\": 1}\n{\"a\": 2}\n");
+
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.arg(".a");
+    cmd.arg(&path);
+
+    cmd.assert().code(0).stdout(".a: 1\n.a: 2\n");
+}
+
+#[test]
+fn invalid_document() {
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.arg(".a");
+    cmd.write_stdin("{\"a\": 1} {\"a\": ");
+
+    cmd.assert().code(3).stdout(".a: 1\n").stderr(
+        "(standard input): line 1, column 15: Invalid JSON: EOF while parsing a value\n  \
+         {\"a\": 1} {\"a\": \n                ^\n",
+    );
+}
+
+#[test]
+fn stream_flag() {
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.args([".id", "--stream"]);
+    cmd.write_stdin("{\"items\": [{\"id\": 1, \"sub\": {\"id\": [2]}}, {\"id\": 3}]} {\"id\": 4}");
+
+    cmd.assert()
+        .code(0)
+        .stdout(".items[0].id: 1\n.items[0].sub.id: [2]\n.items[1].id: 3\n.id: 4\n");
+}
+
+#[test]
+fn stream_count_and_limit() {
+    let input = "[{\"id\": 1}, {\"id\": 2}, {\"id\": 3}] [{\"id\": 4}]";
+
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.args([".id", "--stream", "-c"]);
+    cmd.write_stdin(input);
+    cmd.assert().code(0).stdout("4\n");
+
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.args([".id", "--stream", "-o", "-m", "1", "--count-per", "document"]);
+    cmd.write_stdin(input);
+    cmd.assert().code(0).stdout("1\n4\n");
+}
+
+#[test]
+fn stream_invalid_document() {
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.args([".a", "--stream"]);
+    cmd.write_stdin("{\"a\": 1, \"b\": [}");
+
+    cmd.assert()
+        .code(3)
+        .stdout(".a: 1\n")
+        .stderr("(standard input): line 1, column 16: Invalid JSON\n");
+}
+
+#[test]
+fn stream_json_printer() {
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.args([".a", "--stream", "-p", "json"]);
+    cmd.write_stdin("{\"a\": 1}");
+
+    cmd.assert().code(2);
+}
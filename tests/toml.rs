@@ -0,0 +1,63 @@
+use assert_cmd::Command;
+use common::test_dir::TestDir;
+
+mod common {
+    pub mod test_dir;
+}
+
+const MANIFEST: &str = r#"
+[package]
+name = "app"
+published = 2024-05-01T10:30:00Z
+
+[dependencies]
+serde = { version = "1.0", features = ["derive"] }
+"#;
+
+#[test]
+fn toml_extension() {
+    let dir = TestDir::new("toml");
+    let path = dir.write("Cargo.toml", MANIFEST);
+
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.arg(".dependencies.serde.version");
+    cmd.arg(path);
+
+    cmd.assert()
+        .code(0)
+        .stdout(".dependencies.serde.version: \"1.0\"\n");
+}
+
+#[test]
+fn toml_datetime() {
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.args([".published", "--input", "toml"]);
+    cmd.write_stdin(MANIFEST);
+
+    cmd.assert()
+        .code(0)
+        .stdout(".package.published: \"2024-05-01T10:30:00Z\"\n");
+}
+
+#[test]
+fn invalid_toml() {
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.args([".a", "--input", "toml"]);
+    cmd.write_stdin("a = \n");
+
+    cmd.assert().code(3).stdout("").stderr(
+        "(standard input): line 1, column 5: Invalid TOML: invalid string, expected `\"`, `'`\n  \
+         a = \n      ^\n",
+    );
+}
+
+#[test]
+fn toml_key_order() {
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.args([".*", "--input", "toml"]);
+    cmd.write_stdin("zeta = 1\nalpha = 2\n\n[table]\ny = 3\nx = 4\n");
+
+    cmd.assert()
+        .code(0)
+        .stdout(".zeta: 1\n.alpha: 2\n.table: {\"y\":3,\"x\":4}\n.table.y: 3\n.table.x: 4\n");
+}
@@ -0,0 +1,59 @@
+use assert_cmd::Command;
+use common::test_dir::TestDir;
+
+mod common {
+    pub mod test_dir;
+}
+
+const MANIFESTS: &str = "\
+kind: Service
+metadata:
+  name: web
+---
+kind: Deployment
+spec:
+  replicas: 3
+";
+
+#[test]
+fn yaml_extension() {
+    let dir = TestDir::new("yaml");
+    let path = dir.write("manifests.yaml", MANIFESTS);
+
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.arg(".kind");
+    cmd.arg(&path);
+
+    cmd.assert()
+        .code(0)
+        .stdout(".kind: \"Service\"\n.kind: \"Deployment\"\n");
+}
+
+#[test]
+fn yaml_printers() {
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.args([".replicas", "--input", "yaml", "-o"]);
+    cmd.write_stdin(MANIFESTS);
+    cmd.assert().code(0).stdout("3\n");
+
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.args(["name: web", "--input", "yaml", "-j"]);
+    cmd.write_stdin(MANIFESTS.split("---").next().unwrap());
+    cmd.assert()
+        .code(0)
+        .stdout("{\n  \"metadata\": {\n    \"name\": \"web\"\n  }\n}\n");
+}
+
+#[test]
+fn invalid_yaml() {
+    let mut cmd = Command::cargo_bin("jgrep").unwrap();
+    cmd.args([".a", "--input", "yaml"]);
+    cmd.write_stdin("a: 1\n---\na: [1\n");
+
+    cmd.assert()
+        .code(3)
+        .stdout(".a: 1\n")
+        .stderr(
+            "(standard input): line 4, column 1: Invalid YAML: did not find expected ',' or ']'\n",
+        );
+}